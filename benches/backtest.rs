@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::hint::black_box;
+use std::io::Write;
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate};
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
+use zip::write::SimpleFileOptions;
+
+use crypto_strategy_analysis::account::{Account, Position};
+use crypto_strategy_analysis::backtest::loop_kline;
+use crypto_strategy_analysis::data::{generate_klines, parse_binance_kline, read_zip_file, BinanceKline};
+use crypto_strategy_analysis::traders::{DCATrader, GenericTrader, HODLTrader, MACDTrader, MacdConfig, SMA2Trader, SMATrader, StakeSize, TradingFee};
+
+const CANDLES: usize = 100_000;
+const KLINE_LINE: &str = "1635739200000,4191.50000000,4320.00000000,4146.30000000,4302.93000000,88831.99690000,1635753599999,376834938.78850900,216236,45666.95420000,193846769.34658200,0";
+
+fn synthetic_klines() -> Result<Vec<BinanceKline>> {
+    let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Invalid start time"))?;
+    Ok(generate_klines(CANDLES, start_time, Duration::hours(1), 42))
+}
+
+fn run_backtest<T: GenericTrader>(trader: &mut T, name: &str, klines: &[BinanceKline]) -> Result<Account> {
+    let first_kline = klines.first().ok_or(anyhow!("No klines generated"))?;
+    let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, first_kline.start_time);
+    loop_kline(trader, &mut account, name, klines)?;
+    Ok(account)
+}
+
+fn bench_trader<T, F>(group: &mut BenchmarkGroup<WallTime>, name: &str, klines: &[BinanceKline], new_trader: F)
+where
+    T: GenericTrader,
+    F: Fn(&[BinanceKline]) -> crypto_strategy_analysis::error::Result<T>,
+{
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            let mut trader = new_trader(klines).expect("trader should initialise");
+            black_box(run_backtest(&mut trader, name, klines).expect("backtest should run"))
+        })
+    });
+}
+
+fn bench_loop_kline(c: &mut Criterion) {
+    let klines = synthetic_klines().expect("synthetic klines should generate");
+    let trading_fee = TradingFee::PercentageFee(0.005);
+
+    let mut group = c.benchmark_group("loop_kline");
+    group.throughput(Throughput::Elements(klines.len() as u64));
+    group.sample_size(10);
+
+    bench_trader(&mut group, "MACD", &klines, |klines| MACDTrader::new(klines, trading_fee.clone(), StakeSize::FixPercentage(1.), MacdConfig::default()));
+    bench_trader(&mut group, "HODL", &klines, |klines| HODLTrader::new(klines, trading_fee.clone(), StakeSize::FixPercentage(1.)));
+    bench_trader(&mut group, "DCA", &klines, |klines| DCATrader::new(klines, trading_fee.clone(), StakeSize::FixAmount(100.0)));
+    bench_trader(&mut group, "SMA", &klines, |klines| SMATrader::new(klines, trading_fee.clone(), StakeSize::FixAmount(100.0), 1, 2));
+    bench_trader(&mut group, "SMA2", &klines, |klines| SMA2Trader::new(klines, trading_fee.clone(), StakeSize::FixAmount(100.0), 1, 2));
+
+    group.finish();
+}
+
+fn zipped_klines(klines: &[BinanceKline]) -> Result<File> {
+    let mut content = String::new();
+    for kline in klines {
+        let start_time = kline.start_time.and_utc().timestamp_millis();
+        let end_time = kline.end_time.and_utc().timestamp_millis();
+        content.push_str(&format!("{start_time},{},{},{},{},{},{end_time},0,0,0,0,0\n", kline.open, kline.high, kline.low, kline.close, kline.volume));
+    }
+
+    let mut writer = zip::ZipWriter::new(tempfile::tempfile()?);
+    writer.start_file("klines.csv", SimpleFileOptions::default())?;
+    writer.write_all(content.as_bytes())?;
+    Ok(writer.finish()?)
+}
+
+fn bench_parsing(c: &mut Criterion) {
+    let klines = synthetic_klines().expect("synthetic klines should generate");
+    let zip_file = zipped_klines(&klines).expect("zip file should be written");
+
+    let mut group = c.benchmark_group("parsing");
+
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("parse_binance_kline", |b| b.iter(|| parse_binance_kline(black_box(KLINE_LINE)).expect("line should parse")));
+
+    group.throughput(Throughput::Elements(klines.len() as u64));
+    group.sample_size(10);
+    group.bench_function("read_zip_file", |b| {
+        b.iter(|| {
+            let source = zip_file.try_clone().expect("zip file handle should clone");
+            black_box(read_zip_file(source).expect("zip file should read"))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_loop_kline, bench_parsing);
+criterion_main!(benches);
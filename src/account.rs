@@ -1,19 +1,96 @@
-use anyhow::Ok;
-use anyhow::{anyhow, Result};
-use chrono::NaiveDateTime;
+use crate::error::{Error, Result};
+use crate::metrics::{max_drawdown, sharpe_ratio, Annualisation, DrawdownWindow};
+use crate::report::round_trip_report;
+use chrono::{Duration, NaiveDateTime};
+use log::warn;
+
+// Rolling window exchanges measure traded volume over for fee tiers
+const FEE_VOLUME_WINDOW_DAYS: i64 = 30;
 
 pub struct Account {
     pub available_fund: f64,
     pub position: Position,
     pub profit_and_loss_history: Vec<TimeValue>,
     pub trade_history: Vec<Trade>,
+    pub take_profit_equity: Option<f64>,
+    pub stop_loss_equity: Option<f64>,
+    pub halted_at: Option<NaiveDateTime>,
+    /// When the current position was opened from flat, `None` while flat
+    pub entry_time: Option<NaiveDateTime>,
+    pub oversell_policy: OversellPolicy,
+    pub profit_handling: ProfitHandling,
+    pub withdrawn: f64,
+    pub pnl_denomination: PnlDenomination,
+    starting_equity: f64,
+}
+
+/// What `close` does when asked to sell more than the held quantity.
+/// This never opens a short, the excess is either dropped or rejected.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OversellPolicy {
+    /// Sell only the held quantity and log a warning
+    #[default]
+    Clamp,
+    /// Reject the close without touching the account
+    Error,
+}
+
+/// What happens to realised gains once a position is closed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ProfitHandling {
+    /// Proceeds go back into `available_fund` and size future trades
+    #[default]
+    Reinvest,
+    /// Realised capital above the starting equity moves to `withdrawn` and is never traded again
+    Sweep,
+}
+
+/// Whether the equity history opens with a synthetic zero-PnL point at the start timestamp.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum InitialMark {
+    #[default]
+    Record,
+    /// Leave the history empty until the first real mark, which immediately follows in a backtest
+    Suppress,
+}
+
+/// Unit realised and unrealised PnL are measured in, the cash and equity of the account follow it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PnlDenomination {
+    /// Linear contracts and spot, `quantity * (exit - entry)` in the quote asset
+    #[default]
+    Quote,
+    /// Inverse coin-margined contracts, `quantity * (1 / entry - 1 / exit)` in the base asset.
+    /// The fund is base asset margin, fills only settle their realised PnL and fees must be given in the base asset.
+    Base,
+}
+
+impl PnlDenomination {
+    pub fn pnl(&self, quantity: f64, entry_price: f64, exit_price: f64) -> f64 {
+        if quantity == 0. {
+            return 0.;
+        }
+        match self {
+            PnlDenomination::Quote => quantity * (exit_price - entry_price),
+            PnlDenomination::Base => quantity * (1. / entry_price - 1. / exit_price),
+        }
+    }
+
+    // Cash moved by a fill changing the position by `quantity`, negative for a sale, before fees
+    fn cash_flow(&self, quantity: f64, price: f64, realised_pnl: f64) -> f64 {
+        match self {
+            PnlDenomination::Quote => -quantity * price,
+            PnlDenomination::Base => realised_pnl,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct TimeValue {
-    timestamp: NaiveDateTime,
-    realised_pnl: f64,
-    unrealised_pnl: f64,
+    pub timestamp: NaiveDateTime,
+    pub realised_pnl: f64,
+    pub unrealised_pnl: f64,
+    pub equity: f64,
 }
 
 #[derive(Debug, PartialEq)]
@@ -24,67 +101,323 @@ pub struct Position {
 
 #[derive(Debug, PartialEq)]
 pub struct Trade {
-    timestamp: NaiveDateTime,
-    buy_sell_indicator: BuySellIndicator,
-    quantity: f64,
-    price: f64,
-    fee: f64,
+    pub timestamp: NaiveDateTime,
+    pub buy_sell_indicator: BuySellIndicator,
+    pub quantity: f64,
+    pub price: f64,
+    pub intended_price: f64,
+    pub fee: f64,
+}
+
+impl Trade {
+    // Positive when the fill was worse than the price the trader intended to trade at
+    pub fn slippage_cost(&self) -> f64 {
+        match self.buy_sell_indicator {
+            BuySellIndicator::Buy => (self.price - self.intended_price) * self.quantity,
+            BuySellIndicator::Sell => (self.intended_price - self.price) * self.quantity,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
-enum BuySellIndicator {
+pub enum BuySellIndicator {
     Buy,
     Sell,
 }
 
 impl Account {
     pub fn new(fund: f64, initial_position: Position, start_timestamp: NaiveDateTime) -> Self {
-        let initial_pnl = TimeValue { timestamp: start_timestamp, realised_pnl: 0., unrealised_pnl: 0. };
-        Self { available_fund: fund, position: initial_position, profit_and_loss_history: vec![initial_pnl], trade_history: Vec::new() }
+        let equity = fund + initial_position.quantity * initial_position.cost;
+        let initial_pnl = TimeValue { timestamp: start_timestamp, realised_pnl: 0., unrealised_pnl: 0., equity };
+        Self {
+            available_fund: fund,
+            position: initial_position,
+            profit_and_loss_history: vec![initial_pnl],
+            trade_history: Vec::new(),
+            take_profit_equity: None,
+            stop_loss_equity: None,
+            halted_at: None,
+            entry_time: None,
+            oversell_policy: OversellPolicy::default(),
+            profit_handling: ProfitHandling::default(),
+            withdrawn: 0.,
+            pnl_denomination: PnlDenomination::default(),
+            starting_equity: equity,
+        }
+    }
+
+    pub fn with_equity_guards(mut self, take_profit_equity: Option<f64>, stop_loss_equity: Option<f64>) -> Self {
+        self.take_profit_equity = take_profit_equity;
+        self.stop_loss_equity = stop_loss_equity;
+        self
+    }
+
+    pub fn with_oversell_policy(mut self, oversell_policy: OversellPolicy) -> Self {
+        self.oversell_policy = oversell_policy;
+        self
+    }
+
+    pub fn with_profit_handling(mut self, profit_handling: ProfitHandling) -> Self {
+        self.profit_handling = profit_handling;
+        self
+    }
+
+    pub fn with_pnl_denomination(mut self, pnl_denomination: PnlDenomination) -> Self {
+        self.pnl_denomination = pnl_denomination;
+        // An inverse position holds no notional in the fund, so the starting equity is the margin alone
+        if self.trade_history.is_empty() {
+            self.starting_equity = self.equity_at(self.position.cost);
+            if let [initial_pnl] = self.profit_and_loss_history.as_mut_slice() {
+                initial_pnl.equity = self.starting_equity;
+            }
+        }
+        self
+    }
+
+    pub fn with_initial_mark(mut self, initial_mark: InitialMark) -> Self {
+        if initial_mark == InitialMark::Suppress && self.trade_history.is_empty() {
+            self.profit_and_loss_history.clear();
+        }
+        self
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted_at.is_some()
+    }
+
+    fn equity_at(&self, price: f64) -> f64 {
+        let position_value = match self.pnl_denomination {
+            PnlDenomination::Quote => self.position.quantity * price,
+            PnlDenomination::Base => self.pnl_denomination.pnl(self.position.quantity, self.position.cost, price),
+        };
+        self.available_fund + self.withdrawn + position_value
+    }
+
+    fn equity_guard_breached(&self, equity: f64) -> bool {
+        self.take_profit_equity.is_some_and(|take_profit| equity >= take_profit) || self.stop_loss_equity.is_some_and(|stop_loss| equity <= stop_loss)
+    }
+
+    /// Whether marking at `price` would trip the take-profit or stop-loss equity guard
+    pub fn equity_guard_breached_at(&self, price: f64) -> bool {
+        !self.is_halted() && self.equity_guard_breached(self.equity_at(price))
+    }
+
+    fn ensure_finite(timestamp: NaiveDateTime, operation: &str, values: &[(&str, f64)]) -> Result<()> {
+        match values.iter().find(|(_, value)| !value.is_finite()) {
+            Some((name, value)) => Err(Error::InvalidInput(format!("{timestamp}, {operation} received non-finite {name}: {value}"))),
+            None => Ok(()),
+        }
+    }
+
+    // Realised capital is the cash plus the cost basis still held, only the part above the starting equity is swept
+    fn sweep_profits(&mut self) {
+        if self.profit_handling != ProfitHandling::Sweep {
+            return;
+        }
+        let held_cost = match self.pnl_denomination {
+            PnlDenomination::Quote => self.position.quantity * self.position.cost,
+            PnlDenomination::Base => 0.,
+        };
+        let excess = (self.available_fund + held_cost - self.starting_equity).min(self.available_fund);
+        if excess > 0. {
+            self.available_fund -= excess;
+            self.withdrawn += excess;
+        }
+    }
+
+    // PnL carried forward from the latest mark, zero before the first one when the initial mark is suppressed
+    fn last_pnl(&self) -> (f64, f64) {
+        self.profit_and_loss_history.last().map_or((0., 0.), |time_value| (time_value.realised_pnl, time_value.unrealised_pnl))
     }
 
-    fn average_cost(&self, quantity: f64, price: f64) -> f64 {
-        (self.position.quantity * self.position.cost + quantity * price) / (self.position.quantity + quantity)
+    // Falls back to the fill price when nothing ends up held, e.g. a zero quantity open from flat, instead of a NaN cost
+    fn average_cost(held: f64, cost: f64, quantity: f64, price: f64) -> f64 {
+        let total = held + quantity;
+        if total == 0. {
+            return price;
+        }
+        (held * cost + quantity * price) / total
     }
 
-    pub fn open(&mut self, timestamp: NaiveDateTime, quantity: f64, price: f64, fee: f64) {
-        self.position.cost = self.average_cost(quantity, price);
+    pub fn open(&mut self, timestamp: NaiveDateTime, quantity: f64, price: f64, fee: f64) -> Result<()> {
+        self.open_at_fill(timestamp, quantity, price, price, fee)
+    }
+
+    pub fn open_at_fill(&mut self, timestamp: NaiveDateTime, quantity: f64, intended_price: f64, price: f64, fee: f64) -> Result<()> {
+        Self::ensure_finite(timestamp, "open", &[("quantity", quantity), ("price", price), ("intended_price", intended_price), ("fee", fee)])?;
+        if self.position.quantity == 0. {
+            self.entry_time = Some(timestamp);
+        }
+        self.position.cost = Self::average_cost(self.position.quantity, self.position.cost, quantity, price);
         self.position.quantity += quantity;
-        self.available_fund -= price * quantity + fee;
+        self.available_fund += self.pnl_denomination.cash_flow(quantity, price, 0.) - fee;
+
+        self.trade_history.push(Trade { timestamp, buy_sell_indicator: BuySellIndicator::Buy, quantity, price, intended_price, fee });
 
-        self.trade_history.push(Trade { timestamp, buy_sell_indicator: BuySellIndicator::Buy, quantity, price, fee });
+        Ok(())
     }
 
-    pub fn close(&mut self, timestamp: NaiveDateTime, quantity: f64, price: f64, fee: f64) -> Result<()> {
-        let last_pnl = self.profit_and_loss_history.last().ok_or(anyhow!("No PnL history"))?;
-        let current_pnl = quantity * (price - self.position.cost);
-        let realised_pnl = last_pnl.realised_pnl + current_pnl;
-        let unrealised_pnl = last_pnl.unrealised_pnl - current_pnl;
-        let new_pnl = TimeValue { timestamp, realised_pnl, unrealised_pnl };
+    pub fn open_short(&mut self, timestamp: NaiveDateTime, quantity: f64, price: f64, fee: f64) -> Result<()> {
+        self.open_short_at_fill(timestamp, quantity, price, price, fee)
+    }
+
+    /// Sells `quantity` that is not held, crediting the proceeds and taking `position.quantity` below zero
+    pub fn open_short_at_fill(&mut self, timestamp: NaiveDateTime, quantity: f64, intended_price: f64, price: f64, fee: f64) -> Result<()> {
+        Self::ensure_finite(timestamp, "open_short", &[("quantity", quantity), ("price", price), ("intended_price", intended_price), ("fee", fee)])?;
+        if self.position.quantity > 0. {
+            return Err(Error::InvalidInput(format!("{timestamp}, cannot open a short while holding {}", self.position.quantity)));
+        }
+        let short = -self.position.quantity;
+        if short == 0. {
+            self.entry_time = Some(timestamp);
+        }
+        self.position.cost = Self::average_cost(short, self.position.cost, quantity, price);
+        self.position.quantity -= quantity;
+        self.available_fund += self.pnl_denomination.cash_flow(-quantity, price, 0.) - fee;
+
+        self.trade_history.push(Trade { timestamp, buy_sell_indicator: BuySellIndicator::Sell, quantity, price, intended_price, fee });
+
+        Ok(())
+    }
+
+    pub fn close_short(&mut self, timestamp: NaiveDateTime, quantity: f64, price: f64, fee: f64) -> Result<()> {
+        self.close_short_at_fill(timestamp, quantity, price, price, fee)
+    }
+
+    /// Buys back `quantity` of a short, realising `quantity * (cost - price)`
+    pub fn close_short_at_fill(&mut self, timestamp: NaiveDateTime, quantity: f64, intended_price: f64, price: f64, fee: f64) -> Result<()> {
+        Self::ensure_finite(timestamp, "close_short", &[("quantity", quantity), ("price", price), ("intended_price", intended_price), ("fee", fee)])?;
+        let short = (-self.position.quantity).max(0.);
+        let quantity = if quantity > short {
+            match self.oversell_policy {
+                OversellPolicy::Clamp => {
+                    warn!("{timestamp}, cover of {quantity} exceeds short {short}, clamping");
+                    short
+                }
+                OversellPolicy::Error => return Err(Error::InsufficientFunds(format!("{timestamp}, cover of {quantity} exceeds short {short}"))),
+            }
+        } else {
+            quantity
+        };
+        if quantity <= 0. {
+            return Ok(());
+        }
+        let (last_realised_pnl, last_unrealised_pnl) = self.last_pnl();
+        let current_pnl = self.pnl_denomination.pnl(-quantity, self.position.cost, price);
+        let realised_pnl = last_realised_pnl + current_pnl;
+        let unrealised_pnl = last_unrealised_pnl - current_pnl;
+
+        self.position.quantity += quantity;
+        self.available_fund += self.pnl_denomination.cash_flow(quantity, price, current_pnl) - fee;
+        if self.position.quantity == 0. {
+            self.entry_time = None;
+        }
+        self.sweep_profits();
+
+        let equity = self.equity_at(price);
+        let new_pnl = TimeValue { timestamp, realised_pnl, unrealised_pnl, equity };
         self.profit_and_loss_history.push(new_pnl);
 
+        self.trade_history.push(Trade { timestamp, buy_sell_indicator: BuySellIndicator::Buy, quantity, price, intended_price, fee });
+
+        Ok(())
+    }
+
+    pub fn close(&mut self, timestamp: NaiveDateTime, quantity: f64, price: f64, fee: f64) -> Result<()> {
+        self.close_at_fill(timestamp, quantity, price, price, fee)
+    }
+
+    pub fn close_at_fill(&mut self, timestamp: NaiveDateTime, quantity: f64, intended_price: f64, price: f64, fee: f64) -> Result<()> {
+        Self::ensure_finite(timestamp, "close", &[("quantity", quantity), ("price", price), ("intended_price", intended_price), ("fee", fee)])?;
+        let held = self.position.quantity.max(0.);
+        let quantity = if quantity > held {
+            match self.oversell_policy {
+                OversellPolicy::Clamp => {
+                    warn!("{timestamp}, close of {quantity} exceeds held {held}, clamping");
+                    held
+                }
+                OversellPolicy::Error => return Err(Error::InsufficientFunds(format!("{timestamp}, close of {quantity} exceeds held {held}"))),
+            }
+        } else {
+            quantity
+        };
+        // Nothing held, e.g. a sell signal while flat, so there is no trade to record
+        if quantity <= 0. {
+            return Ok(());
+        }
+        let (last_realised_pnl, last_unrealised_pnl) = self.last_pnl();
+        let current_pnl = self.pnl_denomination.pnl(quantity, self.position.cost, price);
+        let realised_pnl = last_realised_pnl + current_pnl;
+        let unrealised_pnl = last_unrealised_pnl - current_pnl;
+
         self.position.quantity -= quantity;
-        self.available_fund += price * quantity - fee;
+        self.available_fund += self.pnl_denomination.cash_flow(-quantity, price, current_pnl) - fee;
+        if self.position.quantity == 0. {
+            self.entry_time = None;
+        }
+        self.sweep_profits();
+
+        let equity = self.equity_at(price);
+        let new_pnl = TimeValue { timestamp, realised_pnl, unrealised_pnl, equity };
+        self.profit_and_loss_history.push(new_pnl);
 
-        self.trade_history.push(Trade { timestamp, buy_sell_indicator: BuySellIndicator::Sell, quantity, price, fee });
+        self.trade_history.push(Trade { timestamp, buy_sell_indicator: BuySellIndicator::Sell, quantity, price, intended_price, fee });
 
         Ok(())
     }
 
     pub fn mark_to_market(&mut self, timestamp: NaiveDateTime, closing_price: f64) -> Result<()> {
-        let last_pnl = self.profit_and_loss_history.last().ok_or(anyhow!("No PnL history"))?;
-        let unrealised_pnl = self.position.quantity * (closing_price - self.position.cost);
-        let new_pnl = TimeValue { timestamp, unrealised_pnl, realised_pnl: last_pnl.realised_pnl };
+        Self::ensure_finite(timestamp, "mark_to_market", &[("closing_price", closing_price)])?;
+        let (realised_pnl, _) = self.last_pnl();
+        let unrealised_pnl = self.pnl_denomination.pnl(self.position.quantity, self.position.cost, closing_price);
+        let equity = self.equity_at(closing_price);
+        let new_pnl = TimeValue { timestamp, unrealised_pnl, realised_pnl, equity };
         self.profit_and_loss_history.push(new_pnl);
 
+        // The risk desk flattens at the mark price without charging a fee and no further trading is allowed
+        if !self.is_halted() && self.equity_guard_breached(equity) {
+            warn!("{timestamp}, equity {equity:.02} breached account limits, flattening and halting trading");
+            let pre_flatten = self.profit_and_loss_history.len() - 1;
+            if self.position.quantity > 0. {
+                self.close(timestamp, self.position.quantity, closing_price, 0.)?;
+            } else if self.position.quantity < 0. {
+                self.close_short(timestamp, -self.position.quantity, closing_price, 0.)?;
+            }
+            // The flattening trade marks the same timestamp, keep only its entry so the curve has one value per mark
+            if self.profit_and_loss_history.len() > pre_flatten + 1 {
+                self.profit_and_loss_history.remove(pre_flatten);
+            }
+            self.halted_at = Some(timestamp);
+        }
+
         Ok(())
     }
+
+    /// Notional traded in the 30 days up to `timestamp`, which volume tiered fees are priced from
+    pub fn recent_volume(&self, timestamp: NaiveDateTime) -> f64 {
+        let window_start = timestamp - Duration::days(FEE_VOLUME_WINDOW_DAYS);
+        self.trade_history.iter().rev().take_while(|trade| trade.timestamp > window_start).map(|trade| trade.quantity * trade.price).sum()
+    }
+
+    pub fn round_trip_pnls(&self) -> Vec<f64> {
+        round_trip_report(&self.trade_history).iter().map(|round_trip| round_trip.net_pnl).collect()
+    }
+
+    /// Largest peak-to-trough fall of the marked equity as a fraction of the peak, zero while equity never falls
+    pub fn max_drawdown(&self) -> f64 {
+        max_drawdown(&self.profit_and_loss_history, &self.trade_history, DrawdownWindow::Overall)
+    }
+
+    /// Sharpe ratio of the marked equity, annualised from the candle interval or an explicit number of periods
+    pub fn sharpe_ratio(&self, annualisation: Annualisation) -> Option<f64> {
+        sharpe_ratio(&self.profit_and_loss_history, annualisation)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anyhow::Ok;
+    use anyhow::{anyhow, Ok, Result};
     use chrono::NaiveDate;
 
     fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
@@ -107,10 +440,13 @@ mod tests {
         let start_timestamp = create_timestamp(2021, 9, 1)?;
         let mut account = Account::new(7000.0, initial_position, start_timestamp);
         let timestamp = create_timestamp(2021, 10, 31)?;
-        account.open(timestamp, 100.0, 20.0, 0.02);
+        account.open(timestamp, 100.0, 20.0, 0.02)?;
         assert_eq!(account.position, Position { cost: 15.0, quantity: 200.0 });
         assert_eq!(4999.98, account.available_fund);
-        assert_eq!(vec![Trade { timestamp: create_timestamp(2021, 10, 31)?, buy_sell_indicator: BuySellIndicator::Buy, quantity: 100.0, price: 20.0, fee: 0.02 }], account.trade_history);
+        assert_eq!(
+            vec![Trade { timestamp: create_timestamp(2021, 10, 31)?, buy_sell_indicator: BuySellIndicator::Buy, quantity: 100.0, price: 20.0, intended_price: 20.0, fee: 0.02 }],
+            account.trade_history
+        );
 
         Ok(())
     }
@@ -124,7 +460,10 @@ mod tests {
         account.close(timestamp, 50.0, 20.0, 0.02)?;
         assert_eq!(account.position, Position { cost: 10.0, quantity: 50.0 });
         assert_eq!(account.available_fund, 1999.98);
-        assert_eq!(vec![Trade { timestamp: create_timestamp(2021, 10, 31)?, buy_sell_indicator: BuySellIndicator::Sell, quantity: 50.0, price: 20.0, fee: 0.02 }], account.trade_history);
+        assert_eq!(
+            vec![Trade { timestamp: create_timestamp(2021, 10, 31)?, buy_sell_indicator: BuySellIndicator::Sell, quantity: 50.0, price: 20.0, intended_price: 20.0, fee: 0.02 }],
+            account.trade_history
+        );
 
         Ok(())
     }
@@ -135,10 +474,181 @@ mod tests {
         let start_timestamp = create_timestamp(2021, 9, 1)?;
         let mut account = Account::new(5000.0, initial_position, start_timestamp);
         let timestamp = create_timestamp(2021, 10, 31)?;
-        account.mark_to_market(timestamp.clone(), 20.0)?;
+        account.mark_to_market(timestamp, 20.0)?;
 
         let latest_pnl = account.profit_and_loss_history.last().ok_or(anyhow!("No PnL history"))?;
-        assert_eq!(*latest_pnl, TimeValue { timestamp, realised_pnl: 0., unrealised_pnl: 1000. });
+        assert_eq!(*latest_pnl, TimeValue { timestamp, realised_pnl: 0., unrealised_pnl: 1000., equity: 7000. });
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse_pnl_is_denominated_in_base() -> Result<()> {
+        let start_timestamp = create_timestamp(2021, 9, 1)?;
+        let timestamp = create_timestamp(2021, 10, 31)?;
+        let pnls = |pnl_denomination: PnlDenomination| -> Result<(f64, f64)> {
+            let mut account = Account::new(5000.0, Position { quantity: 100.0, cost: 10.0 }, start_timestamp).with_pnl_denomination(pnl_denomination);
+            account.mark_to_market(timestamp, 20.0)?;
+            let unrealised_pnl = account.profit_and_loss_history.last().ok_or(anyhow!("No PnL history"))?.unrealised_pnl;
+            account.close(timestamp, 50.0, 20.0, 0.0)?;
+            let realised_pnl = account.profit_and_loss_history.last().ok_or(anyhow!("No PnL history"))?.realised_pnl;
+            Ok((unrealised_pnl, realised_pnl))
+        };
+
+        assert_eq!(pnls(PnlDenomination::Quote)?, (1000.0, 500.0));
+
+        // 100 contracts * (1 / 10 - 1 / 20) = 5 base units, half of it realised by the close
+        let (unrealised_pnl, realised_pnl) = pnls(PnlDenomination::Base)?;
+        assert!((unrealised_pnl - 5.0).abs() < 1e-12);
+        assert!((realised_pnl - 2.5).abs() < 1e-12);
+        assert_eq!(PnlDenomination::Base.pnl(0.0, 0.0, 20.0), 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_inverse_equity_follows_base_pnl() -> Result<()> {
+        let start_timestamp = create_timestamp(2021, 9, 1)?;
+        let timestamp = create_timestamp(2021, 10, 31)?;
+        let mut account = Account::new(1.0, Position { quantity: 100.0, cost: 10.0 }, start_timestamp).with_pnl_denomination(PnlDenomination::Base).with_equity_guards(None, Some(0.5));
+        assert_eq!(account.profit_and_loss_history[0].equity, 1.0);
+
+        account.mark_to_market(timestamp, 20.0)?;
+        account.close(timestamp, 50.0, 20.0, 0.0)?;
+        account.mark_to_market(timestamp, 20.0)?;
+
+        // Cash only moves by the realised base PnL, so equity is the margin plus realised and unrealised PnL
+        assert!((account.available_fund - 3.5).abs() < 1e-12);
+        for time_value in &account.profit_and_loss_history[1..] {
+            assert!((time_value.equity - (1.0 + time_value.realised_pnl + time_value.unrealised_pnl)).abs() < 1e-12);
+        }
+        assert!(!account.equity_guard_breached_at(10.0));
+        assert!(account.equity_guard_breached_at(5.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_equity_guard_flatten_replaces_mark() -> Result<()> {
+        let start_timestamp = create_timestamp(2021, 9, 1)?;
+        let timestamp = create_timestamp(2021, 10, 31)?;
+        let mut account = Account::new(0.0, Position { quantity: 10.0, cost: 100.0 }, start_timestamp).with_equity_guards(None, Some(900.0));
+
+        account.mark_to_market(timestamp, 80.0)?;
+
+        assert!(account.is_halted());
+        assert_eq!(account.profit_and_loss_history.len(), 2);
+        let last = account.profit_and_loss_history.last().ok_or(anyhow!("No pnl history"))?;
+        assert_eq!((last.timestamp, last.realised_pnl, last.unrealised_pnl, last.equity), (timestamp, -200.0, 0.0, 800.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_finite_inputs_are_rejected() -> Result<()> {
+        let start_timestamp = create_timestamp(2021, 9, 1)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_timestamp);
+        let timestamp = create_timestamp(2021, 10, 31)?;
+
+        let error = account.open(timestamp, 1.0, f64::NAN, 0.0).err().ok_or(anyhow!("NaN price was accepted"))?;
+        assert!(error.to_string().contains("price"));
+        assert!(account.close(timestamp, f64::INFINITY, 20.0, 0.0).is_err());
+        assert!(account.mark_to_market(timestamp, f64::NAN).is_err());
+
+        assert_eq!(account.available_fund, 1000.0);
+        assert_eq!(account.position, Position { quantity: 0.0, cost: 0.0 });
+        assert!(account.trade_history.is_empty());
+        assert_eq!(account.profit_and_loss_history.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_open_mark_and_cover() -> Result<()> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, timestamp);
+
+        account.open_short(timestamp, 2.0, 100.0, 1.0)?;
+        account.open_short(timestamp, 2.0, 110.0, 1.0)?;
+        assert_eq!(account.position, Position { quantity: -4.0, cost: 105.0 });
+        assert_eq!(account.available_fund, 1418.0);
+        assert!(Account::new(1000.0, Position { quantity: 1.0, cost: 100.0 }, timestamp).open_short(timestamp, 1.0, 100.0, 0.0).is_err());
+
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, timestamp);
+        account.open_short(timestamp, 4.0, 105.0, 0.0)?;
+        account.mark_to_market(create_timestamp(2024, 1, 2)?, 95.0)?;
+        let mark = account.profit_and_loss_history.last().ok_or(anyhow!("No mark"))?;
+        assert_eq!(mark.unrealised_pnl, 40.0);
+        assert_eq!(mark.equity, 1040.0);
+
+        account.close_short(create_timestamp(2024, 1, 3)?, 4.0, 90.0, 2.0)?;
+        assert_eq!(account.position.quantity, 0.0);
+        assert_eq!(account.available_fund, 1058.0);
+        assert_eq!(account.profit_and_loss_history.last().map(|time_value| time_value.realised_pnl), Some(60.0));
+        assert_eq!(account.trade_history.last().map(|trade| &trade.buy_sell_indicator), Some(&BuySellIndicator::Buy));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_quantity_open_keeps_cost_finite() -> Result<()> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, timestamp);
+
+        account.open(timestamp, 0.0, 100.0, 0.0)?;
+        assert!(account.position.cost.is_finite());
+        account.mark_to_market(timestamp, 110.0)?;
+        assert!(account.profit_and_loss_history.iter().all(|time_value| time_value.unrealised_pnl.is_finite() && time_value.equity.is_finite()));
+
+        account.open_short(timestamp, 0.0, 100.0, 0.0)?;
+        assert!(account.position.cost.is_finite());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_oversell_is_clamped_or_rejected() -> Result<()> {
+        let start_timestamp = create_timestamp(2021, 9, 1)?;
+        let timestamp = create_timestamp(2021, 10, 31)?;
+
+        let mut account = Account::new(1000.0, Position { quantity: 100.0, cost: 10.0 }, start_timestamp);
+        account.close(timestamp, 150.0, 20.0, 0.0)?;
+        assert_eq!(account.position.quantity, 0.0);
+        assert_eq!(account.available_fund, 3000.0);
+        assert_eq!(account.trade_history[0].quantity, 100.0);
+
+        let mut account = Account::new(1000.0, Position { quantity: 100.0, cost: 10.0 }, start_timestamp).with_oversell_policy(OversellPolicy::Error);
+        assert!(account.close(timestamp, 150.0, 20.0, 0.0).is_err());
+        assert_eq!(account.position.quantity, 100.0);
+        assert_eq!(account.available_fund, 1000.0);
+        assert!(account.trade_history.is_empty());
+
+        let mut flat = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_timestamp);
+        flat.close(timestamp, 1.0, 20.0, 0.0)?;
+        assert_eq!(flat.position.quantity, 0.0);
+        assert_eq!(flat.available_fund, 1000.0);
+        assert!(flat.trade_history.is_empty());
+        assert_eq!(flat.profit_and_loss_history.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_drawdown_of_equity_curve() -> Result<()> {
+        let start_timestamp = create_timestamp(2024, 1, 1)?;
+        let mut account = Account::new(0.0, Position { quantity: 10.0, cost: 100.0 }, start_timestamp);
+        assert_eq!(account.max_drawdown(), 0.0);
+
+        for (day, price) in [120.0, 90.0, 150.0, 135.0].iter().enumerate() {
+            account.mark_to_market(start_timestamp + Duration::days(day as i64 + 1), *price)?;
+        }
+        assert!((account.max_drawdown() - 0.25).abs() < 1e-12);
+
+        let mut rising = Account::new(1000.0, Position { quantity: 1.0, cost: 100.0 }, start_timestamp);
+        for day in 1..4 {
+            rising.mark_to_market(start_timestamp + Duration::days(day), 100.0 + day as f64)?;
+        }
+        assert_eq!(rising.max_drawdown(), 0.0);
 
         Ok(())
     }
@@ -0,0 +1,512 @@
+use crate::account::{Account, Position, TimeValue};
+use crate::data::{BinanceKline, TimestampConvention};
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::metrics::P2Quantile;
+use crate::traders::GenericTrader;
+use chrono::NaiveDateTime;
+use log::info;
+use std::thread;
+use yata::core::Action;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EquityStart {
+    /// Track equity from the first candle
+    #[default]
+    FirstCandle,
+    /// Track equity once the trader's indicator has warmed up, the warm-up candles are still marked and guarded
+    AfterWarmUp,
+    /// Track equity from a fixed candle, e.g. the longest warm-up among compared strategies
+    Candle(usize),
+}
+
+/// Price each candle's equity mark is taken at, which also drives the equity guards.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MarkPrice {
+    #[default]
+    Close,
+    /// Longs at the low and shorts at the high, the worst price the candle traded at
+    Conservative,
+    /// Halfway between the high and the low
+    Mid,
+}
+
+impl MarkPrice {
+    pub fn price(&self, kline: &BinanceKline, quantity: f64) -> f64 {
+        match self {
+            MarkPrice::Close => kline.close,
+            MarkPrice::Conservative if quantity > 0. => kline.low,
+            MarkPrice::Conservative if quantity < 0. => kline.high,
+            MarkPrice::Conservative => kline.close,
+            MarkPrice::Mid => (kline.high + kline.low) / 2.,
+        }
+    }
+}
+
+type OnCandle<'a> = dyn FnMut(&Account, &BinanceKline) + 'a;
+
+pub struct Backtest<'a, T: GenericTrader + ?Sized> {
+    trader: &'a mut T,
+    account: &'a mut Account,
+    last_equity: Option<f64>,
+    return_quantile: Option<P2Quantile>,
+    equity_start: usize,
+    curve_start: Option<usize>,
+    candle_index: usize,
+    mark_price: MarkPrice,
+    on_candle: Option<&'a mut OnCandle<'a>>,
+}
+
+impl<'a, T: GenericTrader + ?Sized> Backtest<'a, T> {
+    pub fn new(trader: &'a mut T, account: &'a mut Account) -> Self {
+        let last_equity = account.profit_and_loss_history.last().map(|time_value| time_value.equity);
+        Self { trader, account, last_equity, return_quantile: None, equity_start: 0, curve_start: None, candle_index: 0, mark_price: MarkPrice::default(), on_candle: None }
+    }
+
+    pub fn with_equity_start(mut self, equity_start: EquityStart) -> Self {
+        self.equity_start = match equity_start {
+            EquityStart::FirstCandle => 0,
+            EquityStart::AfterWarmUp => self.trader.warm_up_period(),
+            EquityStart::Candle(index) => index,
+        };
+        self
+    }
+
+    pub fn with_mark_price(mut self, mark_price: MarkPrice) -> Self {
+        self.mark_price = mark_price;
+        self
+    }
+
+    /// Observes the account after every mark to market, e.g. to feed a live dashboard without post-processing the history
+    pub fn with_on_candle(mut self, on_candle: &'a mut OnCandle<'a>) -> Self {
+        self.on_candle = Some(on_candle);
+        self
+    }
+
+    pub fn with_return_quantile(mut self, p: f64) -> Result<Self> {
+        self.return_quantile = Some(P2Quantile::new(p)?);
+        Ok(self)
+    }
+
+    pub fn step(&mut self, kline: &BinanceKline) -> Result<()> {
+        if self.candle_index == self.equity_start {
+            self.curve_start = Some(self.account.profit_and_loss_history.len().saturating_sub(1));
+        }
+        self.trader.next_trade_session(self.account, kline)?;
+        self.candle_index += 1;
+        let price = self.mark_price.price(kline, self.account.position.quantity);
+        self.account.mark_to_market(kline.timestamp(self.trader.settings().timestamp_convention), price)?;
+
+        let equity = self.account.profit_and_loss_history.last().map(|time_value| time_value.equity);
+        if let (Some(estimator), Some(previous), Some(current)) = (self.return_quantile.as_mut(), self.last_equity, equity) {
+            if previous != 0. {
+                estimator.push(current / previous - 1.);
+            }
+        }
+        self.last_equity = equity;
+
+        if let Some(on_candle) = self.on_candle.as_mut() {
+            on_candle(self.account, kline);
+        }
+
+        Ok(())
+    }
+
+    /// Equity history from the equity start on, opening with the last warm-up mark as its baseline.
+    /// Metrics and reports should use this slice, the account history still holds every mark.
+    pub fn equity_curve(&self) -> &[TimeValue] {
+        let history = &self.account.profit_and_loss_history;
+        self.curve_start.map_or(&[], |curve_start| &history[curve_start..])
+    }
+
+    // Approximate per-candle return quantile, e.g. the 5% quantile as a historical VaR
+    pub fn return_quantile(&self) -> Option<f64> {
+        self.return_quantile.as_ref().and_then(P2Quantile::estimate)
+    }
+}
+
+pub fn loop_kline<T>(trader: &mut T, account: &mut Account, name: &str, klines: &[BinanceKline]) -> Result<()>
+where
+    T: GenericTrader + ?Sized,
+{
+    info!("Running backtest {name}");
+    let mut backtest = Backtest::new(trader, account);
+    for kline in klines {
+        backtest.step(kline)?;
+    }
+
+    Ok(())
+}
+
+/// Feeds the candles through an indicator alone and keeps its first signal per candle, e.g. for entry and exit markers on a chart.
+/// No account is involved, so stops, guards and position policies that would alter trades are not applied.
+pub fn collect_signals(indicator: &mut dyn BinanceIndicatorInstance, klines: &[BinanceKline]) -> Vec<(NaiveDateTime, Action)> {
+    let timestamp_convention = TimestampConvention::default();
+    indicator.set_timestamp_convention(timestamp_convention);
+    klines.iter().map(|kline| (kline.timestamp(timestamp_convention), indicator.next_binance_kline(kline).signals().first().copied().unwrap_or(Action::None))).collect()
+}
+
+/// Builds a boxed trader over the klines it is about to be backtested on
+pub type TraderFactory = Box<dyn Fn(&[BinanceKline]) -> Result<Box<dyn GenericTrader>> + Send + Sync>;
+
+/// Named trader factories run through the same backtest loop, built-in and user strategies alike.
+#[derive(Default)]
+pub struct StrategyRegistry {
+    strategies: Vec<(String, TraderFactory)>,
+}
+
+impl StrategyRegistry {
+    pub fn register<T, F>(self, name: &str, new_trader: F) -> Self
+    where
+        T: GenericTrader + 'static,
+        F: Fn(&[BinanceKline]) -> Result<T> + Send + Sync + 'static,
+    {
+        self.register_boxed(name, Box::new(move |klines| Ok(Box::new(new_trader(klines)?))))
+    }
+
+    pub fn register_boxed(mut self, name: &str, new_trader: TraderFactory) -> Self {
+        self.strategies.push((name.to_string(), new_trader));
+        self
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.strategies.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Backtests every strategy on its own thread and account starting with `start_fund` in cash, results come back in registration order
+    pub fn run_all(&self, klines: &[BinanceKline], start_fund: f64) -> Result<Vec<(String, Result<Account>)>> {
+        let first_kline = klines.first().ok_or(Error::NoData(String::from("No klines to backtest")))?;
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .strategies
+                .iter()
+                .map(|(name, new_trader)| {
+                    scope.spawn(move || {
+                        let mut account = Account::new(start_fund, Position { quantity: 0.0, cost: 0.0 }, first_kline.start_time);
+                        let result = new_trader(klines).and_then(|mut trader| loop_kline(trader.as_mut(), &mut account, name, klines)).map(|_| account);
+                        (name.clone(), result)
+                    })
+                })
+                .collect();
+            // Joining in spawn order keeps the results in registration order, a panicking strategy panics the caller as before
+            handles.into_iter().map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+        });
+        Ok(results)
+    }
+}
+
+pub fn run_seeds<T, F>(klines: &[BinanceKline], seeds: &[u64], start_fund: f64, new_trader: F) -> Result<Vec<f64>>
+where
+    T: GenericTrader,
+    F: Fn(u64) -> Result<T>,
+{
+    let first_kline = klines.first().ok_or(Error::NoData(String::from("No klines to backtest")))?;
+    let mut final_equities = Vec::with_capacity(seeds.len());
+    for seed in seeds {
+        let mut trader = new_trader(*seed)?;
+        let mut account = Account::new(start_fund, Position { quantity: 0.0, cost: 0.0 }, first_kline.start_time);
+        loop_kline(&mut trader, &mut account, &format!("seed {seed}"), klines)?;
+        let last_pnl = account.profit_and_loss_history.last().ok_or(Error::NoData(format!("No pnl history for seed {seed}")))?;
+        final_equities.push(last_pnl.equity);
+    }
+
+    let mean = final_equities.iter().sum::<f64>() / final_equities.len().max(1) as f64;
+    let min = final_equities.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = final_equities.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    info!("Final equity over {} seeds: min {min:.02}, mean {mean:.02}, max {max:.02}", seeds.len());
+
+    Ok(final_equities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::InitialMark;
+    use crate::data::{generate_klines, klines_from_closes};
+    use crate::indicators::SmaPair;
+    use crate::traders::{HODLTrader, Slippage, StakeSize, TraderSettings, TradingFee};
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use yata::core::IndicatorResult;
+    use yata::prelude::*;
+
+    #[test]
+    fn test_run_seeds_is_reproducible() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 110.0, 120.0], start_time, Duration::hours(1));
+        let new_trader = |seed: u64| -> crate::error::Result<HODLTrader> {
+            let settings = TraderSettings { slippage: Slippage::StochasticFill { bps_stddev: 20.0, seed }, ..Default::default() };
+            Ok(HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?.with_settings(settings))
+        };
+
+        let final_equities = run_seeds(&klines, &[1, 1, 2], 1000.0, new_trader)?;
+
+        assert_eq!(final_equities[0], final_equities[1]);
+        assert_ne!(final_equities[0], final_equities[2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_step_streams_return_quantile() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(2000, start_time, Duration::hours(1), 3);
+        let mut trader = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        let mut backtest = Backtest::new(&mut trader, &mut account).with_return_quantile(0.05)?;
+        for kline in &klines {
+            backtest.step(kline)?;
+        }
+        let estimate = backtest.return_quantile().ok_or(anyhow!("No quantile estimate"))?;
+
+        let mut returns: Vec<f64> = account.profit_and_loss_history.windows(2).map(|pair| pair[1].equity / pair[0].equity - 1.).collect();
+        returns.sort_by(f64::total_cmp);
+        let exact = returns[(0.05 * (returns.len() - 1) as f64).round() as usize];
+
+        assert!(estimate < 0.);
+        assert!((estimate - exact).abs() < 0.0005);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_on_candle_sees_every_mark() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 110.0, 120.0, 130.0], start_time, Duration::hours(1));
+        let mut trader = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        let mut seen: Vec<(NaiveDateTime, f64)> = Vec::new();
+        let mut on_candle = |account: &Account, kline: &BinanceKline| {
+            let equity = account.profit_and_loss_history.last().map_or(0., |time_value| time_value.equity);
+            seen.push((kline.timestamp(TimestampConvention::default()), equity));
+        };
+        let mut backtest = Backtest::new(&mut trader, &mut account).with_on_candle(&mut on_candle);
+        for kline in &klines {
+            backtest.step(kline)?;
+        }
+
+        assert_eq!(seen.len(), klines.len());
+        assert!(seen.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(seen.last().map(|(_, equity)| *equity), Some(1300.0));
+
+        Ok(())
+    }
+
+    struct LongSmaTrader {
+        settings: TraderSettings,
+        indicator: Box<dyn BinanceIndicatorInstance>,
+    }
+
+    impl GenericTrader for LongSmaTrader {
+        fn stake_size(&self) -> StakeSize {
+            StakeSize::FixPercentage(1.)
+        }
+
+        fn trading_fee(&self) -> TradingFee {
+            TradingFee::PercentageFee(0.0)
+        }
+
+        fn settings(&self) -> &TraderSettings {
+            &self.settings
+        }
+
+        fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+            self.indicator.as_mut()
+        }
+
+        fn determine_trade(&self, signals: &[Action]) -> crate::error::Result<Action> {
+            Ok(signals.first().copied().unwrap_or(Action::None))
+        }
+    }
+
+    #[test]
+    fn test_conservative_mark_lowers_long_unrealised_pnl() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let mut klines = klines_from_closes(&[100.0, 110.0], start_time, Duration::hours(1));
+        klines[1].low = 95.0;
+        let unrealised_pnl = |mark_price: MarkPrice| -> Result<f64> {
+            let mut trader = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+            let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+            let mut backtest = Backtest::new(&mut trader, &mut account).with_mark_price(mark_price);
+            for kline in &klines {
+                backtest.step(kline)?;
+            }
+            account.profit_and_loss_history.last().map(|time_value| time_value.unrealised_pnl).ok_or(anyhow!("No equity marks"))
+        };
+
+        assert_eq!(unrealised_pnl(MarkPrice::Close)?, 100.0);
+        assert_eq!(unrealised_pnl(MarkPrice::Conservative)?, -50.0);
+        assert_eq!(unrealised_pnl(MarkPrice::Mid)?, 25.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_signals_marks_sma_cross() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let mut closes: Vec<f64> = (0..31).map(|day| 200.0 - day as f64).collect();
+        closes.push(300.0);
+        let klines = klines_from_closes(&closes, start_time, Duration::days(1));
+        let mut indicator = SmaPair::new(2, 5).init(&klines[0])?;
+
+        let signals = collect_signals(&mut indicator, &klines);
+
+        assert_eq!(signals.len(), 32);
+        assert_eq!(signals[31], (klines[31].end_time, Action::Buy(1)));
+        assert!(signals[..31].iter().all(|(_, action)| *action == Action::None));
+
+        Ok(())
+    }
+
+    struct AlwaysBuy;
+
+    impl BinanceIndicatorInstance for AlwaysBuy {
+        fn next_binance_kline(&mut self, _candle: &BinanceKline) -> IndicatorResult {
+            IndicatorResult::new(&[], &[Action::Buy(1)])
+        }
+    }
+
+    // Custom strategy defined outside the crate's traders, buys with all its cash on every candle
+    struct AlwaysBuyTrader {
+        settings: TraderSettings,
+        indicator: AlwaysBuy,
+    }
+
+    impl GenericTrader for AlwaysBuyTrader {
+        fn stake_size(&self) -> StakeSize {
+            StakeSize::FixPercentage(1.)
+        }
+
+        fn trading_fee(&self) -> TradingFee {
+            TradingFee::PercentageFee(0.0)
+        }
+
+        fn settings(&self) -> &TraderSettings {
+            &self.settings
+        }
+
+        fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+            &mut self.indicator
+        }
+
+        fn determine_trade(&self, signals: &[Action]) -> crate::error::Result<Action> {
+            Ok(signals.first().copied().unwrap_or(Action::None))
+        }
+    }
+
+    #[test]
+    fn test_registry_runs_custom_strategy() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 100.0, 100.0], start_time, Duration::hours(1));
+        let registry = StrategyRegistry::default()
+            .register("HODL", |klines| HODLTrader::new(klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.)))
+            .register("AlwaysBuy", |_| Ok(AlwaysBuyTrader { settings: TraderSettings::default(), indicator: AlwaysBuy }));
+
+        let results = registry.run_all(&klines, 1000.0)?;
+
+        assert_eq!(registry.names(), vec!["HODL", "AlwaysBuy"]);
+        let (name, account) = &results[1];
+        let account = account.as_ref().map_err(|error| anyhow!("{name} failed: {error}"))?;
+        assert!(results[0].1.is_ok());
+        assert_eq!(account.trade_history.len(), 1);
+        assert_eq!(account.position.quantity, 10.0);
+        assert_eq!(account.available_fund, 0.0);
+
+        Ok(())
+    }
+
+    fn equity_timestamps<T: GenericTrader>(trader: &mut T, klines: &[BinanceKline], equity_start: EquityStart) -> Result<Vec<NaiveDateTime>> {
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, klines[0].start_time);
+        let mut backtest = Backtest::new(trader, &mut account).with_equity_start(equity_start);
+        for kline in klines {
+            backtest.step(kline)?;
+        }
+        Ok(backtest.equity_curve().iter().skip(1).map(|time_value| time_value.timestamp).collect())
+    }
+
+    #[test]
+    fn test_equity_start_after_warm_up_and_aligned() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(300, start_time, Duration::hours(1), 11);
+
+        let mut sma = LongSmaTrader { settings: TraderSettings::default(), indicator: Box::new(SmaPair::new(50, 200).init(&klines[0])?) };
+        let mut hodl = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+        assert_eq!(sma.warm_up_period(), 200);
+        assert_eq!(hodl.warm_up_period(), 0);
+
+        let sma_curve = equity_timestamps(&mut sma, &klines, EquityStart::AfterWarmUp)?;
+        let hodl_curve = equity_timestamps(&mut hodl, &klines, EquityStart::AfterWarmUp)?;
+        assert_eq!(sma_curve.first(), Some(&klines[200].end_time));
+        assert_eq!(hodl_curve.first(), Some(&klines[0].end_time));
+
+        let mut hodl = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+        let aligned_curve = equity_timestamps(&mut hodl, &klines, EquityStart::Candle(200))?;
+        assert_eq!(aligned_curve.first(), sma_curve.first());
+        assert_eq!(aligned_curve.len(), sma_curve.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_equity_guard_fires_during_warm_up() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 100.0, 80.0, 80.0, 120.0, 120.0], start_time, Duration::hours(1));
+        let mut hodl = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time).with_equity_guards(None, Some(900.0));
+
+        let mut backtest = Backtest::new(&mut hodl, &mut account).with_equity_start(EquityStart::Candle(4));
+        for kline in &klines {
+            backtest.step(kline)?;
+        }
+        let curve: Vec<f64> = backtest.equity_curve().iter().map(|time_value| time_value.equity).collect();
+
+        // The stop loss flattens on the third candle although equity is only reported from the fifth
+        assert_eq!(curve, vec![800.0, 800.0, 800.0]);
+        assert_eq!(account.halted_at, Some(klines[2].end_time));
+        assert_eq!(account.position.quantity, 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_equity_timestamps_follow_convention() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(48, start_time, Duration::hours(1), 17);
+
+        for convention in [TimestampConvention::OpenTime, TimestampConvention::CloseTime] {
+            let settings = TraderSettings { timestamp_convention: convention, ..Default::default() };
+            let mut hodl = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?.with_settings(settings);
+            let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+            loop_kline(&mut hodl, &mut account, "HODL", &klines)?;
+
+            let expected: Vec<chrono::NaiveDateTime> = klines.iter().map(|kline| kline.timestamp(convention)).collect();
+            let equity_timestamps: Vec<chrono::NaiveDateTime> = account.profit_and_loss_history.iter().skip(1).map(|time_value| time_value.timestamp).collect();
+            assert_eq!(equity_timestamps, expected);
+            assert_eq!(account.trade_history[0].timestamp, klines[0].timestamp(convention));
+        }
+        assert_eq!(TimestampConvention::default(), TimestampConvention::CloseTime);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_suppressed_initial_mark_leaves_one_point_per_candle() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(24, start_time, Duration::hours(1), 5);
+
+        for (initial_mark, expected_len) in [(InitialMark::Record, klines.len() + 1), (InitialMark::Suppress, klines.len())] {
+            let mut hodl = HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?;
+            let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time).with_initial_mark(initial_mark);
+            loop_kline(&mut hodl, &mut account, "HODL", &klines)?;
+            assert_eq!(account.profit_and_loss_history.len(), expected_len);
+        }
+
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time).with_initial_mark(InitialMark::Suppress);
+        loop_kline(&mut HODLTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.))?, &mut account, "HODL", &klines)?;
+        assert_eq!(account.profit_and_loss_history.first().map(|time_value| time_value.timestamp), Some(klines[0].end_time));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use log::LevelFilter;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CliOptions {
+    pub verbosity: i8,
+    /// Exclusive end of the backtest window, defaults to yesterday when omitted
+    pub end_date: Option<NaiveDate>,
+    /// Directory downloaded archives are kept in, defaults to `KlineCache::default_dir` when omitted
+    pub cache_dir: Option<PathBuf>,
+    /// Directory each strategy's fills and round trips are written to as CSV, nothing is written when omitted
+    pub output_dir: Option<PathBuf>,
+}
+
+impl CliOptions {
+    pub fn level_filter(&self) -> LevelFilter {
+        match self.verbosity {
+            i8::MIN..=-2 => LevelFilter::Error,
+            -1 => LevelFilter::Warn,
+            0 => LevelFilter::Info,
+            1 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        }
+    }
+}
+
+pub fn parse_args<I>(args: I) -> Result<CliOptions>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = CliOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quiet" => options.verbosity -= 1,
+            "--verbose" => options.verbosity += 1,
+            "--end" => {
+                let value = args.next().ok_or(anyhow!("Missing value for --end"))?;
+                options.end_date = Some(NaiveDate::parse_from_str(&value, "%Y-%m-%d")?);
+            }
+            "--cache-dir" => {
+                let value = args.next().ok_or(anyhow!("Missing value for --cache-dir"))?;
+                options.cache_dir = Some(PathBuf::from(value));
+            }
+            "--output-dir" => {
+                let value = args.next().ok_or(anyhow!("Missing value for --output-dir"))?;
+                options.output_dir = Some(PathBuf::from(value));
+            }
+            flags if flags.starts_with('-') && !flags.starts_with("--") && flags.len() > 1 => {
+                for flag in flags.chars().skip(1) {
+                    match flag {
+                        'q' => options.verbosity -= 1,
+                        'v' => options.verbosity += 1,
+                        _ => return Err(anyhow!("Unknown flag -{flag}")),
+                    }
+                }
+            }
+            _ => return Err(anyhow!("Unknown argument {arg}")),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn parse(args: &[&str]) -> Result<CliOptions> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_verbosity_flags() -> Result<()> {
+        assert_eq!(parse(&[])?.level_filter(), LevelFilter::Info);
+        assert_eq!(parse(&["--quiet"])?.level_filter(), LevelFilter::Warn);
+        assert_eq!(parse(&["-qq"])?.level_filter(), LevelFilter::Error);
+        assert_eq!(parse(&["-v"])?.level_filter(), LevelFilter::Debug);
+        assert_eq!(parse(&["--verbose", "-v"])?.level_filter(), LevelFilter::Trace);
+        assert!(parse(&["-x"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_end_date() -> Result<()> {
+        assert_eq!(parse(&[])?.end_date, None);
+        assert_eq!(parse(&["--end", "2024-06-01"])?.end_date, NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert!(parse(&["--end"]).is_err());
+        assert!(parse(&["--end", "June"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_dir() -> Result<()> {
+        assert_eq!(parse(&[])?.cache_dir, None);
+        assert_eq!(parse(&["--cache-dir", "/tmp/klines"])?.cache_dir, Some(PathBuf::from("/tmp/klines")));
+        assert!(parse(&["--cache-dir"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_dir() -> Result<()> {
+        assert_eq!(parse(&[])?.output_dir, None);
+        assert_eq!(parse(&["--output-dir", "/tmp/reports"])?.output_dir, Some(PathBuf::from("/tmp/reports")));
+        assert!(parse(&["--output-dir"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_quiet_mode_suppresses_fetch_lines() -> Result<()> {
+        // Per-date fetch lines are logged at info level
+        assert!(parse(&["-q"])?.level_filter() < Level::Info);
+
+        Ok(())
+    }
+}
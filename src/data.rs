@@ -1,2 +1,23 @@
 mod binance;
-pub use binance::{get_kline_data, BinanceKline};
+pub use binance::{
+    build_client, drop_unclosed, get_kline_data, get_kline_data_from, get_kline_data_multi, get_kline_data_multi_from, parse_binance_kline, read_zip_file, BinanceKline, HttpConfig, KlineCache,
+    RetryPolicy, SourcePolicy, TimestampConvention, BINANCE_DATA_URL,
+};
+
+mod local_csv;
+pub use local_csv::{get_kline_data_from_csv, ColumnMap, CsvSchema};
+
+mod synthetic;
+pub use synthetic::{generate_klines, klines_from_closes};
+
+mod interval;
+pub use interval::{check_interval_spacing, Interval};
+
+mod symbol;
+pub use symbol::Symbol;
+
+mod quality;
+pub use quality::{data_quality_report, DataQualityReport};
+
+mod exchange_info;
+pub use exchange_info::{parse_exchange_info, ExchangeInfoCache, SymbolFilters, BINANCE_API_URL};
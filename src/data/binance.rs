@@ -1,53 +1,197 @@
+use std::collections::{BTreeSet, HashMap};
 use std::fs::File;
+use std::future::Future;
 use std::io::prelude::Read;
 use std::io::Cursor;
 use std::iter::Iterator;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use yata::core::OHLCV;
 
 use chrono::prelude::*;
 use chrono::{Duration, NaiveDateTime, Utc};
-use reqwest::{self};
+use futures::stream::{self, StreamExt};
+use reqwest::header::RANGE;
+use reqwest::{self, Client, StatusCode};
 use tempfile::tempfile;
 
-use log::info;
+use crate::data::{check_interval_spacing, Interval, Symbol};
+use log::{info, warn};
 
-use anyhow::{anyhow, Result};
+use crate::error::{Error, Result};
 
-fn is_current_month(year: i32, month: u32) -> bool {
-    let now = Utc::now();
-    let current_year = now.year();
-    let current_month = now.month();
-    year == current_year && month == current_month
+pub const BINANCE_DATA_URL: &str = "https://data.binance.vision";
+
+// Symbols downloaded at the same time by the batch download
+const SYMBOL_CONCURRENCY: usize = 4;
+
+// Archives of one symbol downloaded at the same time
+const DOWNLOAD_CONCURRENCY: usize = 8;
+
+// Downloads observed before the remaining time is estimated from their average latency
+const ETA_AFTER_FILES: usize = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SourcePolicy {
+    /// Monthly archives for past months, daily files only for the current month
+    #[default]
+    PreferMonthly,
+    /// Daily files wherever they exist, falling back to the monthly archive when a day is missing
+    PreferDaily,
 }
 
-fn binance_data_url(symbol: &str, interval: &str, year: i32, month: u32, day: u32) -> String {
-    let folder = if is_current_month(year, month) { "daily" } else { "monthly" };
-    let base_url = format!("https://data.binance.vision/data/spot/{folder}/klines");
-    let file_name = match folder {
-        "daily" => {
-            format!("{symbol}-{interval}-{year}-{month:02}-{day:02}.zip")
-        }
-        "monthly" => format!("{symbol}-{interval}-{year}-{month:02}.zip"),
-        _ => panic!("Not expected folder type"),
+/// Declaration order is the merge priority, a daily file wins over the monthly archive covering the same candle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Folder {
+    Daily,
+    Monthly,
+}
+
+fn is_current_month(date: NaiveDate, today: NaiveDate) -> bool {
+    date.year() == today.year() && date.month() == today.month()
+}
+
+// Location of an archive below the mirror's spot data root, also its location inside a cache directory
+fn archive_path(symbol: &str, interval: &str, folder: Folder, date: NaiveDate) -> String {
+    let (year, month, day) = (date.year(), date.month(), date.day());
+    let (folder, file_name) = match folder {
+        Folder::Daily => ("daily", format!("{symbol}-{interval}-{year}-{month:02}-{day:02}.zip")),
+        Folder::Monthly => ("monthly", format!("{symbol}-{interval}-{year}-{month:02}.zip")),
     };
-    let url = format!("{base_url}/{symbol}/{interval}/{file_name}");
-    url
+    format!("{folder}/klines/{symbol}/{interval}/{file_name}")
+}
+
+// Where an archive is cached, None for the current month whose daily files are still being published and always fetched.
+// Past days and months never change once published, so they are cached forever.
+fn cached_archive_path(cache_dir: Option<&Path>, symbol: &str, interval: &str, folder: Folder, date: NaiveDate, today: NaiveDate) -> Option<PathBuf> {
+    let dir = cache_dir.filter(|_| !is_current_month(date, today))?;
+    Some(dir.join(archive_path(symbol, interval, folder, date)))
+}
+
+// `$XDG_CACHE_HOME`, then `~/.cache`, then the system temp directory
+fn default_cache_dir(xdg_cache_home: Option<PathBuf>, home: Option<PathBuf>) -> PathBuf {
+    let root = xdg_cache_home.or_else(|| home.map(|home| home.join(".cache"))).unwrap_or_else(std::env::temp_dir);
+    root.join("crypto-strategy-analysis")
+}
+
+fn binance_data_url(base_url: &str, symbol: &str, interval: &str, folder: Folder, date: NaiveDate) -> String {
+    format!("{base_url}/data/spot/{}", archive_path(symbol, interval, folder, date))
+}
+
+// The current month has no monthly archive yet, so it is always fetched day by day
+fn source_candidates(policy: SourcePolicy, date: NaiveDate, today: NaiveDate) -> Vec<Folder> {
+    if is_current_month(date, today) {
+        return vec![Folder::Daily];
+    }
+    match policy {
+        SourcePolicy::PreferMonthly => vec![Folder::Monthly],
+        SourcePolicy::PreferDaily => vec![Folder::Daily, Folder::Monthly],
+    }
 }
 
-async fn check_url_exists(url: &str) -> Result<bool> {
-    let response = reqwest::get(url).await?;
-    Ok(response.status().is_success())
+#[derive(Debug, Clone, Copy)]
+pub struct HttpConfig {
+    pub connect_timeout: std::time::Duration,
+    pub read_timeout: std::time::Duration,
 }
 
-async fn download_binance_data_to_file(url: &str, target: &mut File) -> Result<()> {
-    let response = reqwest::get(url).await?;
-    let mut content = Cursor::new(response.bytes().await?);
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self { connect_timeout: std::time::Duration::from_secs(10), read_timeout: std::time::Duration::from_secs(60) }
+    }
+}
+
+/// Retries of a request that failed in transport or with a 5xx response, a missing archive is never retried.
+/// The first retry waits `initial_backoff` and every further one waits twice as long as the previous.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: std::time::Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_backoff: std::time::Duration::from_secs(1) }
+    }
+}
+
+impl RetryPolicy {
+    async fn run<T, F, Fut>(&self, url: &str, mut attempt: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            match attempt().await {
+                Err(Error::Network(error)) if retries < self.max_retries => {
+                    retries += 1;
+                    warn!("{error}, retry {retries} of {} for {url} in {:.1}s", self.max_retries, backoff.as_secs_f64());
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+pub fn build_client(config: &HttpConfig) -> Result<Client> {
+    let client = Client::builder().connect_timeout(config.connect_timeout).read_timeout(config.read_timeout).build()?;
+    Ok(client)
+}
+
+fn request_error(url: &str, error: reqwest::Error) -> Error {
+    if error.is_timeout() {
+        Error::Network(format!("Request to {url} timed out"))
+    } else {
+        Error::from(error)
+    }
+}
+
+// A HEAD request leaves the archive to the download, mirrors rejecting HEAD are asked for its first byte instead
+async fn check_url_exists(client: &Client, url: &str) -> Result<bool> {
+    let response = client.head(url).send().await.map_err(|e| request_error(url, e))?;
+    let response = match response.status() {
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED => client.get(url).header(RANGE, "bytes=0-0").send().await.map_err(|e| request_error(url, e))?,
+        _ => response,
+    };
+    let status = response.status();
+    if status.is_server_error() {
+        return Err(Error::Network(format!("Request to {url} failed with {status}")));
+    }
+    Ok(status.is_success())
+}
+
+async fn download_binance_data_to_file(client: &Client, url: &str, target: &mut File) -> Result<()> {
+    let response = client.get(url).send().await.map_err(|e| request_error(url, e))?;
+    let status = response.status();
+    if status.is_server_error() {
+        return Err(Error::Network(format!("Request to {url} failed with {status}")));
+    }
+    if !status.is_success() {
+        return Err(Error::NoData(format!("Request to {url} failed with {status}")));
+    }
+    let mut content = Cursor::new(response.bytes().await.map_err(|e| request_error(url, e))?);
     std::io::copy(&mut content, target)?;
     Ok(())
 }
 
-fn read_zip_file(source: File) -> Result<String> {
+// Every attempt writes a new file, so a retried download never appends to a failed one
+async fn download_to_tempfile(client: &Client, url: &str) -> Result<File> {
+    let mut temp_file = tempfile()?;
+    download_binance_data_to_file(client, url, &mut temp_file).await?;
+    Ok(temp_file)
+}
+
+async fn download_to_path(client: &Client, url: &str, path: &Path) -> Result<()> {
+    let mut file = File::create(path)?;
+    download_binance_data_to_file(client, url, &mut file).await
+}
+
+pub fn read_zip_file(source: File) -> Result<String> {
     let mut archive = zip::ZipArchive::new(source)?;
     let mut data = archive.by_index(0)?;
     let mut buf = String::new();
@@ -55,6 +199,15 @@ fn read_zip_file(source: File) -> Result<String> {
     Ok(buf)
 }
 
+/// Which end of a candle stamps its signals, trades and equity marks.
+/// Defaults to the close time, the first moment the candle's close is actually known.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TimestampConvention {
+    OpenTime,
+    #[default]
+    CloseTime,
+}
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct BinanceKline {
     pub start_time: NaiveDateTime,
@@ -66,6 +219,15 @@ pub struct BinanceKline {
     pub end_time: NaiveDateTime,
 }
 
+impl BinanceKline {
+    pub fn timestamp(&self, convention: TimestampConvention) -> NaiveDateTime {
+        match convention {
+            TimestampConvention::OpenTime => self.start_time,
+            TimestampConvention::CloseTime => self.end_time,
+        }
+    }
+}
+
 impl OHLCV for BinanceKline {
     fn open(&self) -> f64 {
         self.open
@@ -84,65 +246,277 @@ impl OHLCV for BinanceKline {
     }
 }
 
-fn parse_binance_kline(data: &str) -> Result<Option<BinanceKline>> {
+/// Parses one line of a Binance kline dump: open time, open, high, low, close, volume, close time, then fields not used here.
+pub fn parse_binance_kline(data: &str) -> Result<Option<BinanceKline>> {
     if !data.contains(",") {
         return Ok(None);
     }
     let mut data = data.split(",");
-    let start_time: i64 = data.next().ok_or(anyhow!("Missing start_time"))?.parse()?;
-    let start_time = DateTime::from_timestamp(start_time / 1000, 0).ok_or(anyhow!("Invalid start_time timestamp"))?.naive_utc();
-    let open: f64 = data.next().ok_or(anyhow!("Missing open"))?.parse()?;
-    let close: f64 = data.next().ok_or(anyhow!("Missing close"))?.parse()?;
-    let high: f64 = data.next().ok_or(anyhow!("Missing high"))?.parse()?;
-    let low: f64 = data.next().ok_or(anyhow!("Missing low"))?.parse()?;
-    let volume: f64 = data.next().ok_or(anyhow!("Missing volume"))?.parse()?;
-    let end_time: i64 = data.next().ok_or(anyhow!("Missing end_time"))?.parse()?;
-    let end_time = DateTime::from_timestamp(end_time / 1000, 0).ok_or(anyhow!("Invalid end_time timestamp"))?.naive_utc();
+    let start_time: i64 = data.next().ok_or(Error::Parse(String::from("Missing start_time")))?.parse()?;
+    let start_time = DateTime::from_timestamp(start_time / 1000, 0).ok_or(Error::Parse(String::from("Invalid start_time timestamp")))?.naive_utc();
+    let open: f64 = data.next().ok_or(Error::Parse(String::from("Missing open")))?.parse()?;
+    let high: f64 = data.next().ok_or(Error::Parse(String::from("Missing high")))?.parse()?;
+    let low: f64 = data.next().ok_or(Error::Parse(String::from("Missing low")))?.parse()?;
+    let close: f64 = data.next().ok_or(Error::Parse(String::from("Missing close")))?.parse()?;
+    let volume: f64 = data.next().ok_or(Error::Parse(String::from("Missing volume")))?.parse()?;
+    let end_time: i64 = data.next().ok_or(Error::Parse(String::from("Missing end_time")))?.parse()?;
+    let end_time = DateTime::from_timestamp(end_time / 1000, 0).ok_or(Error::Parse(String::from("Invalid end_time timestamp")))?.naive_utc();
 
     let parsed = BinanceKline { start_time, open, close, high, low, volume, end_time };
     Ok(Some(parsed))
 }
 
-fn advance_date(current_date: NaiveDate) -> Result<NaiveDate> {
-    let next_date = if !is_current_month(current_date.year(), current_date.month()) {
-        if current_date.month() < 12 {
-            NaiveDate::from_ymd_opt(current_date.year(), current_date.month() + 1, 1).ok_or(anyhow!("Invalid date"))?
-        } else {
-            NaiveDate::from_ymd_opt(current_date.year() + 1, 1, 1).ok_or(anyhow!("Invalid date"))?
-        }
-    } else {
-        current_date + Duration::days(1)
+fn advance_date(current_date: NaiveDate, folder: Folder) -> Result<NaiveDate> {
+    let next_date = match folder {
+        Folder::Monthly if current_date.month() < 12 => NaiveDate::from_ymd_opt(current_date.year(), current_date.month() + 1, 1).ok_or(Error::InvalidInput(String::from("Invalid date")))?,
+        Folder::Monthly => NaiveDate::from_ymd_opt(current_date.year() + 1, 1, 1).ok_or(Error::InvalidInput(String::from("Invalid date")))?,
+        Folder::Daily => current_date + Duration::days(1),
     };
     Ok(next_date)
 }
 
-pub async fn get_kline_data(symbol: &str, interval: &str, from: NaiveDate, to: NaiveDate) -> Result<Vec<BinanceKline>> {
+// Monthly archives cover whole months, so drop candles that start on or after the exclusive end date
+fn trim_to_end_date(klines: &mut Vec<BinanceKline>, to: NaiveDate) {
+    let end_time = to.and_time(NaiveTime::MIN);
+    klines.retain(|kline| kline.start_time < end_time);
+}
+
+// A monthly fallback for a missing day covers the whole month, so drop candles that start before the start date
+fn trim_to_start_date(klines: &mut Vec<BinanceKline>, from: NaiveDate) {
+    let start_time = from.and_time(NaiveTime::MIN);
+    klines.retain(|kline| kline.start_time >= start_time);
+}
+
+// Archive standing in for `date` when its preferred file is missing, a monthly archive is keyed by the first of its month
+fn fallback_source(policy: SourcePolicy, date: NaiveDate, today: NaiveDate) -> Option<(Folder, NaiveDate)> {
+    let folder = *source_candidates(policy, date, today).get(1)?;
+    let date = match folder {
+        Folder::Daily => date,
+        Folder::Monthly => date.with_day(1)?,
+    };
+    Some((folder, date))
+}
+
+// Files fetched when every preferred source exists, missing ones add one fallback archive per month
+fn plan_downloads(policy: SourcePolicy, from: NaiveDate, to: NaiveDate, today: NaiveDate) -> Result<Vec<(Folder, NaiveDate)>> {
+    let mut plan = Vec::new();
     let mut cur_date = from;
-    let mut result: Vec<BinanceKline> = Vec::new();
     while cur_date < to {
-        info!("fetching data for date: {cur_date}");
-
-        let url = binance_data_url(symbol, interval, cur_date.year(), cur_date.month(), cur_date.day());
-        let check = check_url_exists(&url).await?;
-        if check {
-            let mut temp_file = tempfile()?;
-            download_binance_data_to_file(&url, &mut temp_file).await?;
-            let content = read_zip_file(temp_file)?;
-            for line in content.split("\n") {
-                if let Some(data) = parse_binance_kline(line)? {
-                    result.push(data)
-                }
+        let folder = *source_candidates(policy, cur_date, today).first().ok_or(Error::NoData(format!("No source for {cur_date}")))?;
+        plan.push((folder, cur_date));
+        cur_date = advance_date(cur_date, folder)?;
+    }
+    Ok(plan)
+}
+
+/// Drops candles still forming at `now`, acting on them repaints once the candle closes differently.
+/// Archive candles are always closed, this only matters for recent data from live sources.
+pub fn drop_unclosed(klines: &mut Vec<BinanceKline>, now: NaiveDateTime, include_unclosed: bool) {
+    if !include_unclosed {
+        klines.retain(|kline| kline.end_time < now);
+    }
+}
+
+// Content of a cached archive, None when it is missing or unreadable, e.g. cut off by an interrupted download
+fn read_cached_zip(path: &Path) -> Option<String> {
+    if !path.exists() {
+        return None;
+    }
+    match File::open(path).map_err(Error::from).and_then(read_zip_file) {
+        Ok(content) => Some(content),
+        Err(error) => {
+            warn!("Cached {} is unreadable, fetching it again: {error}", path.display());
+            None
+        }
+    }
+}
+
+// Content of an archive, None when the mirror has no such file.
+// A cached download lands in a partial file first, so only complete archives ever sit at the cache path.
+async fn fetch_zip(client: &Client, url: &str, cache_path: Option<&Path>, retry: RetryPolicy) -> Result<Option<String>> {
+    if !retry.run(url, || check_url_exists(client, url)).await? {
+        return Ok(None);
+    }
+    let Some(path) = cache_path else {
+        let temp_file = retry.run(url, || download_to_tempfile(client, url)).await?;
+        return read_zip_file(temp_file).map(Some);
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let partial = path.with_extension("zip.part");
+    let partial_path = partial.as_path();
+    retry.run(url, || download_to_path(client, url, partial_path)).await?;
+    std::fs::rename(&partial, path)?;
+    read_zip_file(File::open(path)?).map(Some)
+}
+
+/// Keeps downloaded archives under a directory laid out like the mirror, so an interrupted download resumes where it stopped.
+/// Archives already on disk are read instead of fetched, an unreadable one is fetched again.
+pub struct KlineCache {
+    base_url: String,
+    dir: PathBuf,
+    retry: RetryPolicy,
+}
+
+impl KlineCache {
+    pub fn new(base_url: &str, dir: &Path) -> Self {
+        Self { base_url: base_url.to_string(), dir: dir.to_path_buf(), retry: RetryPolicy::default() }
+    }
+
+    /// `crypto-strategy-analysis` below the user's cache directory, or below the system temp directory without a home
+    pub fn default_dir() -> PathBuf {
+        default_cache_dir(std::env::var_os("XDG_CACHE_HOME").map(PathBuf::from), std::env::var_os("HOME").map(PathBuf::from))
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Same as `get_kline_data`, only the files missing from the cache are downloaded
+    pub async fn get_kline_data(&self, client: &Client, symbol: &Symbol, interval: Interval, from: NaiveDate, to: NaiveDate, policy: SourcePolicy) -> Result<Vec<BinanceKline>> {
+        fetch_klines(client, Archive { base_url: &self.base_url, cache_dir: Some(&self.dir), retry: self.retry }, symbol, interval, from, to, policy).await
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Archive<'a> {
+    base_url: &'a str,
+    cache_dir: Option<&'a Path>,
+    retry: RetryPolicy,
+}
+
+impl Archive<'_> {
+    // Candles of one file tagged with its folder, None when neither the cache nor the mirror has it
+    async fn fetch(self, client: &Client, pair: &str, interval: Interval, folder: Folder, date: NaiveDate, today: NaiveDate) -> Result<Option<Vec<(Folder, BinanceKline)>>> {
+        info!("fetching data for date: {date}");
+        let url = binance_data_url(self.base_url, pair, interval.as_str(), folder, date);
+        let cache_path = cached_archive_path(self.cache_dir, pair, interval.as_str(), folder, date, today);
+        let content = match cache_path.as_deref().and_then(read_cached_zip) {
+            Some(content) => {
+                info!("using cached data for date: {date}");
+                Some(content)
+            }
+            None => fetch_zip(client, &url, cache_path.as_deref(), self.retry).await?,
+        };
+        let Some(content) = content else {
+            return Ok(None);
+        };
+
+        let mut klines = Vec::new();
+        for line in content.split("\n") {
+            if let Some(data) = parse_binance_kline(line)? {
+                klines.push((folder, data));
             }
         }
-        cur_date = advance_date(cur_date)?;
+        Ok(Some(klines))
+    }
+}
+
+/// Orders candles by start time and keeps one per start time, the one from the highest priority source.
+/// The result doesn't depend on the order files arrived in, so repeated downloads are identical.
+fn merge_sources(mut sourced: Vec<(Folder, BinanceKline)>) -> Vec<BinanceKline> {
+    sourced.sort_by_key(|(folder, kline)| (kline.start_time, *folder));
+    sourced.dedup_by_key(|(_, kline)| kline.start_time);
+    sourced.into_iter().map(|(_, kline)| kline).collect()
+}
+
+/// Downloads candles from `from` (inclusive) up to `to` (exclusive).
+pub async fn get_kline_data(client: &Client, symbol: &Symbol, interval: Interval, from: NaiveDate, to: NaiveDate, policy: SourcePolicy) -> Result<Vec<BinanceKline>> {
+    get_kline_data_from(client, BINANCE_DATA_URL, symbol, interval, from, to, policy).await
+}
+
+/// Same as `get_kline_data` against another mirror of the Binance data archive.
+pub async fn get_kline_data_from(client: &Client, base_url: &str, symbol: &Symbol, interval: Interval, from: NaiveDate, to: NaiveDate, policy: SourcePolicy) -> Result<Vec<BinanceKline>> {
+    fetch_klines(client, Archive { base_url, cache_dir: None, retry: RetryPolicy::default() }, symbol, interval, from, to, policy).await
+}
+
+async fn fetch_klines(client: &Client, archive: Archive<'_>, symbol: &Symbol, interval: Interval, from: NaiveDate, to: NaiveDate, policy: SourcePolicy) -> Result<Vec<BinanceKline>> {
+    let pair = symbol.pair();
+    let pair = pair.as_str();
+    let today = Utc::now().date_naive();
+    let plan = plan_downloads(policy, from, to, today)?;
+    let planned = plan.len();
+    info!("Download plan: {planned} files for {symbol} {interval} from {from} to {to}");
+
+    let started = Instant::now();
+    let mut fetched_files = 0;
+    let mut sourced: Vec<(Folder, BinanceKline)> = Vec::new();
+    let mut fallbacks = BTreeSet::new();
+    let mut downloads = stream::iter(plan).map(|(folder, date)| async move { (date, archive.fetch(client, pair, interval, folder, date, today).await) }).buffered(DOWNLOAD_CONCURRENCY);
+    while let Some((date, klines)) = downloads.next().await {
+        match klines? {
+            Some(klines) => sourced.extend(klines),
+            None => fallbacks.extend(fallback_source(policy, date, today)),
+        }
+
+        fetched_files += 1;
+        let remaining = planned.saturating_sub(fetched_files);
+        if fetched_files >= ETA_AFTER_FILES && remaining > 0 {
+            let eta = started.elapsed() / fetched_files as u32 * remaining as u32;
+            info!("Fetched {fetched_files} of {planned} files, about {:.0}s remaining", eta.as_secs_f64());
+        }
     }
+
+    // Days missing from the preferred source share one fallback archive per month
+    let fallback_downloads = stream::iter(fallbacks).map(|(folder, date)| archive.fetch(client, pair, interval, folder, date, today)).buffered(DOWNLOAD_CONCURRENCY).collect::<Vec<_>>().await;
+    for klines in fallback_downloads {
+        sourced.extend(klines?.unwrap_or_default());
+    }
+
+    // Downloads complete in any order and a monthly fallback repeats days already fetched from daily files
+    let mut result = merge_sources(sourced);
+    trim_to_start_date(&mut result, from);
+    trim_to_end_date(&mut result, to);
+    drop_unclosed(&mut result, Utc::now().naive_utc(), false);
+    if result.is_empty() {
+        return Err(Error::NoData(format!("No {symbol} {interval} klines from {from} to {to}")));
+    }
+    check_interval_spacing(&result, interval);
     Ok(result)
 }
 
+/// Downloads every symbol of a basket, see `get_kline_data_multi_from`.
+pub async fn get_kline_data_multi(client: &Client, symbols: &[&str], interval: Interval, from: NaiveDate, to: NaiveDate, policy: SourcePolicy) -> HashMap<String, Result<Vec<BinanceKline>>> {
+    get_kline_data_multi_from(client, BINANCE_DATA_URL, symbols, interval, from, to, policy).await
+}
+
+/// Downloads a few symbols at a time over the shared client, keyed by the symbol as given.
+/// Every symbol gets its own outcome, so one that fails is logged and reported without failing the rest of the batch.
+pub async fn get_kline_data_multi_from(
+    client: &Client,
+    base_url: &str,
+    symbols: &[&str],
+    interval: Interval,
+    from: NaiveDate,
+    to: NaiveDate,
+    policy: SourcePolicy,
+) -> HashMap<String, Result<Vec<BinanceKline>>> {
+    let downloads = stream::iter(symbols).map(|name| async move {
+        let klines = match name.parse::<Symbol>() {
+            Ok(symbol) => get_kline_data_from(client, base_url, &symbol, interval, from, to, policy).await,
+            Err(error) => Err(error),
+        };
+        (name.to_string(), klines)
+    });
+    let results: Vec<(String, Result<Vec<BinanceKline>>)> = downloads.buffer_unordered(SYMBOL_CONCURRENCY).collect().await;
+
+    for (name, klines) in &results {
+        if let Err(error) = klines {
+            warn!("Failed to load {name}: {error}");
+        }
+    }
+    results.into_iter().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
     use chrono::NaiveDate;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     fn create_timestamp(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> Result<NaiveDateTime> {
         NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(hour, minute, second)).ok_or(anyhow!("cannot create timestamp"))
@@ -153,17 +527,619 @@ mod tests {
         let test_string = "1635739200000,4191.50000000,4320.00000000,4146.30000000,4302.93000000,88831.99690000,1635753599999,376834938.78850900,216236,45666.95420000,193846769.34658200,0";
         let result = parse_binance_kline(test_string)?;
         let expected = BinanceKline {
-            start_time: create_timestamp(2021, 11, 01, 4, 0, 0)?,
+            start_time: create_timestamp(2021, 11, 1, 4, 0, 0)?,
             open: 4191.5,
-            close: 4320.0,
-            high: 4146.3,
-            low: 4302.93,
+            close: 4302.93,
+            high: 4320.0,
+            low: 4146.3,
             volume: 88831.9969,
-            end_time: create_timestamp(2021, 11, 01, 7, 59, 59)?,
+            end_time: create_timestamp(2021, 11, 1, 7, 59, 59)?,
         };
 
         assert_eq!(result, Some(expected));
 
         Ok(())
     }
+
+    #[test]
+    fn test_source_selection_at_month_boundary() -> Result<()> {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 10).ok_or(anyhow!("Invalid date"))?;
+        let last_day = NaiveDate::from_ymd_opt(2024, 5, 31).ok_or(anyhow!("Invalid date"))?;
+        let first_day = NaiveDate::from_ymd_opt(2024, 6, 1).ok_or(anyhow!("Invalid date"))?;
+        let urls = |policy: SourcePolicy, date: NaiveDate| -> Vec<String> {
+            source_candidates(policy, date, today).into_iter().map(|folder| binance_data_url(BINANCE_DATA_URL, "ETHUSDT", "1h", folder, date)).collect()
+        };
+
+        let daily = "https://data.binance.vision/data/spot/daily/klines/ETHUSDT/1h/ETHUSDT-1h-2024-05-31.zip";
+        let monthly = "https://data.binance.vision/data/spot/monthly/klines/ETHUSDT/1h/ETHUSDT-1h-2024-05.zip";
+        assert_eq!(urls(SourcePolicy::PreferMonthly, last_day), vec![monthly]);
+        assert_eq!(urls(SourcePolicy::PreferDaily, last_day), vec![daily, monthly]);
+
+        let current = "https://data.binance.vision/data/spot/daily/klines/ETHUSDT/1h/ETHUSDT-1h-2024-06-01.zip";
+        assert_eq!(urls(SourcePolicy::PreferMonthly, first_day), vec![current]);
+        assert_eq!(urls(SourcePolicy::PreferDaily, first_day), vec![current]);
+
+        assert_eq!(advance_date(last_day, Folder::Daily)?, first_day);
+        assert_eq!(advance_date(last_day, Folder::Monthly)?, first_day);
+
+        Ok(())
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Result<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day).ok_or(anyhow!("Invalid date"))
+    }
+
+    #[test]
+    fn test_advance_date_through_leap_day() -> Result<()> {
+        assert_eq!(advance_date(date(2024, 2, 28)?, Folder::Daily)?, date(2024, 2, 29)?);
+        assert_eq!(advance_date(date(2024, 2, 29)?, Folder::Daily)?, date(2024, 3, 1)?);
+        assert_eq!(advance_date(date(2023, 2, 28)?, Folder::Daily)?, date(2023, 3, 1)?);
+        assert_eq!(advance_date(date(2024, 2, 1)?, Folder::Monthly)?, date(2024, 3, 1)?);
+        assert_eq!(advance_date(date(2024, 2, 29)?, Folder::Monthly)?, date(2024, 3, 1)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_advance_date_across_year_end() -> Result<()> {
+        assert_eq!(advance_date(date(2023, 12, 31)?, Folder::Daily)?, date(2024, 1, 1)?);
+        assert_eq!(advance_date(date(2023, 12, 15)?, Folder::Monthly)?, date(2024, 1, 1)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk_covers_every_day_once() -> Result<()> {
+        let today = date(2024, 3, 10)?;
+        let mut cur_date = date(2023, 12, 15)?;
+        let mut covered = Vec::new();
+        while cur_date < today {
+            let folder = *source_candidates(SourcePolicy::PreferMonthly, cur_date, today).last().ok_or(anyhow!("No source"))?;
+            let next_date = advance_date(cur_date, folder)?;
+            covered.extend(cur_date.iter_days().take_while(|day| *day < next_date));
+            cur_date = next_date;
+        }
+
+        let expected: Vec<NaiveDate> = date(2023, 12, 15)?.iter_days().take_while(|day| *day < today).collect();
+        assert_eq!(covered, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_days_are_24_hours_in_utc() -> Result<()> {
+        // Naive UTC dates have no DST, so days around European and US clock changes stay 24 hours long
+        for day in [date(2024, 3, 10)?, date(2024, 3, 31)?, date(2024, 10, 27)?, date(2024, 11, 3)?] {
+            let next_day = advance_date(day, Folder::Daily)?;
+            assert_eq!(next_day.and_time(NaiveTime::MIN) - day.and_time(NaiveTime::MIN), Duration::hours(24));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_to_end_date() -> Result<()> {
+        let closes: Vec<f64> = (0..72).map(|i| 100.0 + i as f64).collect();
+        let mut klines = klines_from_closes(&closes, create_timestamp(2024, 5, 30, 0, 0, 0)?, Duration::hours(1));
+        let end_date = NaiveDate::from_ymd_opt(2024, 6, 1).ok_or(anyhow!("Invalid end date"))?;
+
+        trim_to_end_date(&mut klines, end_date);
+
+        let end_time = create_timestamp(2024, 6, 1, 0, 0, 0)?;
+        assert_eq!(klines.len(), 48);
+        assert!(klines.iter().all(|kline| kline.start_time < end_time));
+
+        Ok(())
+    }
+
+    struct CapturingLogger;
+
+    static CAPTURED: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            if let Ok(mut captured) = CAPTURED.lock() {
+                captured.push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_plan_downloads_counts_files() -> Result<()> {
+        let today = date(2024, 3, 10)?;
+
+        assert_eq!(plan_downloads(SourcePolicy::PreferMonthly, date(2023, 12, 15)?, today, today)?.len(), 3 + 9);
+        assert_eq!(plan_downloads(SourcePolicy::PreferDaily, date(2024, 2, 1)?, date(2024, 3, 3)?, today)?.len(), 31);
+        assert!(plan_downloads(SourcePolicy::PreferDaily, today, today, today)?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_plan_size_is_logged() -> Result<()> {
+        static LOGGER: CapturingLogger = CapturingLogger;
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Info);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            }
+        });
+
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "BTCUSDT".parse()?;
+        let _ = get_kline_data_from(&client, &format!("http://{address}"), &symbol, Interval::FourHours, date(2023, 3, 30)?, date(2023, 4, 4)?, SourcePolicy::PreferDaily).await;
+
+        let captured = CAPTURED.lock().map_err(|_| anyhow!("Log capture poisoned"))?;
+        assert!(captured.iter().any(|line| line == "Download plan: 5 files for BTC/USDT 4h from 2023-03-30 to 2023-04-04"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unclosed_candle_is_dropped_by_default() -> Result<()> {
+        let klines = klines_from_closes(&[100.0, 101.0, 102.0], create_timestamp(2024, 5, 30, 0, 0, 0)?, Duration::hours(1));
+        let now = create_timestamp(2024, 5, 30, 2, 30, 0)?;
+
+        let mut closed = klines.clone();
+        drop_unclosed(&mut closed, now, false);
+        assert_eq!(closed, klines[..2]);
+
+        let mut all = klines.clone();
+        drop_unclosed(&mut all, now, true);
+        assert_eq!(all, klines);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out_on_slow_server() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            if let Ok((_socket, _)) = listener.accept().await {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            }
+        });
+
+        let config = HttpConfig { connect_timeout: std::time::Duration::from_millis(200), read_timeout: std::time::Duration::from_millis(200) };
+        let client = build_client(&config)?;
+        let result = check_url_exists(&client, &format!("http://{address}/ETHUSDT-1h-2024-01.zip")).await;
+
+        let error = result.err().ok_or(anyhow!("Expected the slow request to time out"))?;
+        assert!(error.to_string().contains("timed out"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_existence_check_uses_head_then_ranged_get() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or_default();
+                let request = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let ranged = request.contains("range: bytes=0-0");
+                let response: &[u8] = match (request.starts_with("head"), request.contains("/no-head/")) {
+                    (true, true) => b"HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    (true, false) => b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    (false, _) if ranged => b"HTTP/1.1 206 Partial Content\r\nContent-Length: 1\r\nContent-Range: bytes 0-0/100\r\nConnection: close\r\n\r\nP",
+                    (false, _) => b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                };
+                if let Ok(mut recorded) = recorded.lock() {
+                    recorded.push(request.split_whitespace().next().unwrap_or_default().to_string());
+                }
+                let _ = socket.write_all(response).await;
+            }
+        });
+        let client = build_client(&HttpConfig::default())?;
+        let requested = || -> Result<Vec<String>> {
+            let mut requests = requests.lock().map_err(|_| anyhow!("Request log poisoned"))?;
+            Ok(std::mem::take(&mut *requests))
+        };
+
+        assert!(check_url_exists(&client, &format!("http://{address}/head/ETHUSDT-1h-2024-01.zip")).await?);
+        assert_eq!(requested()?, vec!["head"]);
+
+        assert!(check_url_exists(&client, &format!("http://{address}/no-head/ETHUSDT-1h-2024-01.zip")).await?);
+        assert_eq!(requested()?, vec!["head", "get"]);
+
+        Ok(())
+    }
+
+    fn zipped_csv(lines: &[&str]) -> Result<Vec<u8>> {
+        use std::io::Write;
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer.start_file("klines.csv", zip::write::SimpleFileOptions::default())?;
+        writer.write_all(lines.join("\n").as_bytes())?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    #[tokio::test]
+    async fn test_batch_download_reports_each_symbol() -> Result<()> {
+        let archive = zipped_csv(&["1704067200000,100.0,101.0,99.0,100.5,10.0,1704070799999,0,0,0,0,0"])?;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or_default();
+                let response = if String::from_utf8_lossy(&request[..read]).contains("ETHUSDT") {
+                    [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", archive.len()).into_bytes(), archive.clone()].concat()
+                } else {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                };
+                let _ = socket.write_all(&response).await;
+            }
+        });
+
+        let client = build_client(&HttpConfig::default())?;
+        let klines =
+            get_kline_data_multi_from(&client, &format!("http://{address}"), &["ETHUSDT", "BTCUSDT"], Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 2)?, SourcePolicy::PreferDaily).await;
+
+        assert_eq!(klines.len(), 2);
+        let eth = klines.get("ETHUSDT").ok_or(anyhow!("ETHUSDT missing from the batch"))?.as_ref().map_err(|error| anyhow!("ETHUSDT failed: {error}"))?;
+        assert_eq!(eth.len(), 1);
+        assert_eq!(eth[0].start_time, create_timestamp(2024, 1, 1, 0, 0, 0)?);
+        assert!(matches!(klines.get("BTCUSDT"), Some(Err(Error::NoData(_)))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_archives_are_no_data() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            }
+        });
+
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let result = get_kline_data_from(&client, &format!("http://{address}"), &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 3)?, SourcePolicy::PreferDaily).await;
+
+        assert!(matches!(result, Err(Error::NoData(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_prefers_daily_regardless_of_arrival_order() -> Result<()> {
+        let start = create_timestamp(2024, 1, 1, 0, 0, 0)?;
+        let mut daily = klines_from_closes(&[1.0, 2.0], start, Duration::hours(1));
+        let monthly = klines_from_closes(&[10.0, 20.0, 30.0], start, Duration::hours(1));
+        daily.iter_mut().for_each(|kline| kline.open = 1.0);
+
+        let in_order: Vec<(Folder, BinanceKline)> = daily.iter().map(|k| (Folder::Daily, *k)).chain(monthly.iter().map(|k| (Folder::Monthly, *k))).collect();
+        let reversed: Vec<(Folder, BinanceKline)> = in_order.iter().rev().copied().collect();
+
+        let merged = merge_sources(in_order);
+        assert_eq!(merged, merge_sources(reversed));
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0], daily[0]);
+        assert_eq!(merged[1], daily[1]);
+        assert_eq!(merged[2], monthly[2]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_sources_download_identically() -> Result<()> {
+        let daily = zipped_csv(&["1704067200000,1.0,1.0,1.0,1.0,10.0,1704070799999,0,0,0,0,0"])?;
+        let monthly = zipped_csv(&["1704067200000,2.0,2.0,2.0,2.0,10.0,1704070799999,0,0,0,0,0", "1704153600000,3.0,3.0,3.0,3.0,10.0,1704157199999,0,0,0,0,0"])?;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or_default();
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let archive = if request.contains("monthly") {
+                    Some(&monthly)
+                } else if request.contains("2024-01-01") {
+                    Some(&daily)
+                } else {
+                    None
+                };
+                let response = match archive {
+                    Some(archive) => [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", archive.len()).into_bytes(), archive.clone()].concat(),
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = socket.write_all(&response).await;
+            }
+        });
+
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let base_url = format!("http://{address}");
+        let first = get_kline_data_from(&client, &base_url, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 3)?, SourcePolicy::PreferDaily).await?;
+        let second = get_kline_data_from(&client, &base_url, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 3)?, SourcePolicy::PreferDaily).await?;
+
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].open, 1.0);
+        assert_eq!(first[1].open, 3.0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_monthly_fallback_is_trimmed_to_the_start_date() -> Result<()> {
+        let daily = zipped_csv(&["1704153600000,2.0,2.0,2.0,2.0,10.0,1704157199999,0,0,0,0,0"])?;
+        let monthly = zipped_csv(&[
+            "1704067200000,1.0,1.0,1.0,1.0,10.0,1704070799999,0,0,0,0,0",
+            "1704153600000,9.0,9.0,9.0,9.0,10.0,1704157199999,0,0,0,0,0",
+            "1704240000000,3.0,3.0,3.0,3.0,10.0,1704243599999,0,0,0,0,0",
+        ])?;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or_default();
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let archive = if request.contains("monthly") {
+                    Some(&monthly)
+                } else if request.contains("2024-01-02") {
+                    Some(&daily)
+                } else {
+                    None
+                };
+                let response = match archive {
+                    Some(archive) => [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", archive.len()).into_bytes(), archive.clone()].concat(),
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = socket.write_all(&response).await;
+            }
+        });
+
+        // The daily file of 2024-01-03 is missing, so its monthly archive also carries 2024-01-01
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let klines = get_kline_data_from(&client, &format!("http://{address}"), &symbol, Interval::OneHour, date(2024, 1, 2)?, date(2024, 1, 4)?, SourcePolicy::PreferDaily).await?;
+
+        assert_eq!(klines.iter().map(|kline| kline.open).collect::<Vec<f64>>(), vec![2.0, 3.0]);
+
+        Ok(())
+    }
+
+    // Serves one daily archive per date and records the path of every request
+    async fn serve_daily_archives(days: u32) -> Result<(String, std::sync::Arc<std::sync::Mutex<Vec<String>>>)> {
+        let mut archives = HashMap::new();
+        for day in 0..days {
+            let start = 1704067200000 + day as i64 * 86_400_000;
+            let line = format!("{start},100.0,101.0,99.0,100.5,10.0,{},0,0,0,0,0", start + 3_599_999);
+            archives.insert(format!("ETHUSDT-1h-2024-01-{:02}.zip", day + 1), zipped_csv(&[line.as_str()])?);
+        }
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let recorded = std::sync::Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or_default();
+                let path = String::from_utf8_lossy(&request[..read]).split_whitespace().nth(1).unwrap_or_default().to_string();
+                let archive = path.rsplit('/').next().and_then(|file_name| archives.get(file_name));
+                let response = match archive {
+                    Some(archive) => [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", archive.len()).into_bytes(), archive.clone()].concat(),
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                if let Ok(mut recorded) = recorded.lock() {
+                    recorded.push(path);
+                }
+                let _ = socket.write_all(&response).await;
+            }
+        });
+        Ok((format!("http://{address}"), requests))
+    }
+
+    // Answers the first `failures` requests with a 503, then serves the archive of 2024-01-01 and a 404 for anything else
+    async fn serve_after_failures(failures: usize) -> Result<(String, std::sync::Arc<std::sync::Mutex<Vec<String>>>)> {
+        let archive = zipped_csv(&["1704067200000,100.0,101.0,99.0,100.5,10.0,1704070799999,0,0,0,0,0"])?;
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let recorded = std::sync::Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or_default();
+                let path = String::from_utf8_lossy(&request[..read]).split_whitespace().nth(1).unwrap_or_default().to_string();
+                let seen = recorded.lock().map(|mut recorded| {
+                    recorded.push(path.clone());
+                    recorded.len()
+                });
+                let response = if seen.unwrap_or_default() <= failures {
+                    b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                } else if path.ends_with("2024-01-01.zip") {
+                    [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", archive.len()).into_bytes(), archive.clone()].concat()
+                } else {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                };
+                let _ = socket.write_all(&response).await;
+            }
+        });
+        Ok((format!("http://{address}"), requests))
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried_but_missing_archives_are_not() -> Result<()> {
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let retry = RetryPolicy { max_retries: 2, initial_backoff: std::time::Duration::from_millis(10) };
+        let request_count = |requests: &std::sync::Mutex<Vec<String>>| requests.lock().map(|requests| requests.len()).map_err(|_| anyhow!("Request log poisoned"));
+
+        // Two 503s on the existence check, then the check and the download succeed
+        let (base_url, requests) = serve_after_failures(2).await?;
+        let cache_dir = tempfile::tempdir()?;
+        let klines =
+            KlineCache::new(&base_url, cache_dir.path()).with_retry(retry).get_kline_data(&client, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 2)?, SourcePolicy::PreferDaily).await?;
+        assert_eq!(klines.len(), 1);
+        assert_eq!(request_count(&requests)?, 4);
+
+        // The first attempt and both retries fail
+        let (base_url, requests) = serve_after_failures(3).await?;
+        let cache_dir = tempfile::tempdir()?;
+        let result =
+            KlineCache::new(&base_url, cache_dir.path()).with_retry(retry).get_kline_data(&client, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 2)?, SourcePolicy::PreferDaily).await;
+        assert!(matches!(result, Err(Error::Network(_))));
+        assert_eq!(request_count(&requests)?, 3);
+
+        // The daily file and its monthly fallback are each asked for once
+        let (base_url, requests) = serve_after_failures(0).await?;
+        let cache_dir = tempfile::tempdir()?;
+        let result =
+            KlineCache::new(&base_url, cache_dir.path()).with_retry(retry).get_kline_data(&client, &symbol, Interval::OneHour, date(2024, 1, 2)?, date(2024, 1, 3)?, SourcePolicy::PreferDaily).await;
+        assert!(matches!(result, Err(Error::NoData(_))));
+        assert_eq!(request_count(&requests)?, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_downloads_stay_in_chronological_order() -> Result<()> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut archives = HashMap::new();
+        for day in 0..6u64 {
+            let start = 1704067200000 + day as i64 * 86_400_000;
+            let line = format!("{start},100.0,101.0,99.0,{}.0,10.0,{},0,0,0,0,0", 100 + day, start + 3_599_999);
+            archives.insert(format!("ETHUSDT-1h-2024-01-{:02}.zip", day + 1), (day, zipped_csv(&[line.as_str()])?));
+        }
+        let archives = std::sync::Arc::new(archives);
+        let in_flight = std::sync::Arc::new(AtomicUsize::new(0));
+        let max_in_flight = std::sync::Arc::new(AtomicUsize::new(0));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let (active, peak) = (std::sync::Arc::clone(&in_flight), std::sync::Arc::clone(&max_in_flight));
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (archives, active, peak) = (std::sync::Arc::clone(&archives), std::sync::Arc::clone(&active), std::sync::Arc::clone(&peak));
+                tokio::spawn(async move {
+                    peak.fetch_max(active.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    let mut request = [0u8; 1024];
+                    let read = socket.read(&mut request).await.unwrap_or_default();
+                    let path = String::from_utf8_lossy(&request[..read]).split_whitespace().nth(1).unwrap_or_default().to_string();
+                    let response = match path.rsplit('/').next().and_then(|file_name| archives.get(file_name)) {
+                        Some((day, archive)) => {
+                            // Earlier days answer last, so downloads complete in reverse order
+                            tokio::time::sleep(std::time::Duration::from_millis(30 * (6 - day))).await;
+                            [format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", archive.len()).into_bytes(), archive.clone()].concat()
+                        }
+                        None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                    };
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    let _ = socket.write_all(&response).await;
+                });
+            }
+        });
+
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let klines = get_kline_data_from(&client, &format!("http://{address}"), &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 7)?, SourcePolicy::PreferDaily).await?;
+
+        assert!(max_in_flight.load(Ordering::SeqCst) > 1);
+        assert_eq!(klines.len(), 6);
+        assert!(klines.windows(2).all(|pair| pair[0].start_time < pair[1].start_time));
+        assert_eq!(klines.iter().map(|kline| kline.close).collect::<Vec<f64>>(), vec![100.0, 101.0, 102.0, 103.0, 104.0, 105.0]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resume_fetches_only_dates_missing_from_cache() -> Result<()> {
+        let (base_url, requests) = serve_daily_archives(4).await?;
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let requested = || -> Result<Vec<String>> {
+            let mut requests = requests.lock().map_err(|_| anyhow!("Request log poisoned"))?;
+            Ok(std::mem::take(&mut *requests))
+        };
+
+        let fresh_dir = tempfile::tempdir()?;
+        let fresh = KlineCache::new(&base_url, fresh_dir.path()).get_kline_data(&client, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 5)?, SourcePolicy::PreferDaily).await?;
+        assert_eq!(fresh.len(), 4);
+        requested()?;
+
+        let resumed_dir = tempfile::tempdir()?;
+        for day in ["2024-01-01", "2024-01-02"] {
+            let relative = format!("daily/klines/ETHUSDT/1h/ETHUSDT-1h-{day}.zip");
+            let target = resumed_dir.path().join(&relative);
+            std::fs::create_dir_all(target.parent().ok_or(anyhow!("No cache directory"))?)?;
+            std::fs::copy(fresh_dir.path().join(&relative), target)?;
+        }
+        let resumed = KlineCache::new(&base_url, resumed_dir.path()).get_kline_data(&client, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 5)?, SourcePolicy::PreferDaily).await?;
+
+        assert_eq!(resumed, fresh);
+        let fetched = requested()?;
+        assert!(!fetched.is_empty());
+        assert!(fetched.iter().all(|path| path.ends_with("2024-01-03.zip") || path.ends_with("2024-01-04.zip")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_cached_archive_is_fetched_again() -> Result<()> {
+        let (base_url, requests) = serve_daily_archives(2).await?;
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+
+        let cache_dir = tempfile::tempdir()?;
+        let corrupt = cache_dir.path().join("daily/klines/ETHUSDT/1h/ETHUSDT-1h-2024-01-02.zip");
+        std::fs::create_dir_all(corrupt.parent().ok_or(anyhow!("No cache directory"))?)?;
+        std::fs::write(&corrupt, b"cut off mid-download")?;
+        let klines = KlineCache::new(&base_url, cache_dir.path()).get_kline_data(&client, &symbol, Interval::OneHour, date(2024, 1, 1)?, date(2024, 1, 3)?, SourcePolicy::PreferDaily).await?;
+
+        assert_eq!(klines.len(), 2);
+        assert!(read_cached_zip(&corrupt).is_some());
+        let fetched = requests.lock().map_err(|_| anyhow!("Request log poisoned"))?;
+        assert!(fetched.iter().any(|path| path.ends_with("2024-01-02.zip")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_current_month_is_not_cached() -> Result<()> {
+        let cache_dir = Path::new("/cache");
+        let today = date(2024, 3, 15)?;
+
+        let monthly = cached_archive_path(Some(cache_dir), "ETHUSDT", "1h", Folder::Monthly, date(2024, 2, 1)?, today);
+        assert_eq!(monthly, Some(cache_dir.join("monthly/klines/ETHUSDT/1h/ETHUSDT-1h-2024-02.zip")));
+        let past_day = cached_archive_path(Some(cache_dir), "ETHUSDT", "1h", Folder::Daily, date(2024, 2, 29)?, today);
+        assert_eq!(past_day, Some(cache_dir.join("daily/klines/ETHUSDT/1h/ETHUSDT-1h-2024-02-29.zip")));
+        assert_eq!(cached_archive_path(Some(cache_dir), "ETHUSDT", "1h", Folder::Daily, date(2024, 3, 14)?, today), None);
+        assert_eq!(cached_archive_path(None, "ETHUSDT", "1h", Folder::Monthly, date(2024, 2, 1)?, today), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_cache_dir_prefers_xdg_then_home() {
+        let xdg = Some(PathBuf::from("/xdg"));
+        let home = Some(PathBuf::from("/home/trader"));
+
+        assert_eq!(default_cache_dir(xdg, home.clone()), PathBuf::from("/xdg/crypto-strategy-analysis"));
+        assert_eq!(default_cache_dir(None, home), PathBuf::from("/home/trader/.cache/crypto-strategy-analysis"));
+        assert_eq!(default_cache_dir(None, None), std::env::temp_dir().join("crypto-strategy-analysis"));
+    }
 }
@@ -0,0 +1,121 @@
+use crate::data::Symbol;
+use crate::error::{Error, Result};
+use log::info;
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const BINANCE_API_URL: &str = "https://api.binance.com";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolFilters {
+    pub step_size: f64,
+    pub tick_size: f64,
+    pub min_notional: f64,
+}
+
+impl SymbolFilters {
+    // Quantities round down so an order never exceeds the stake it was sized from
+    pub fn round_quantity(&self, quantity: f64) -> f64 {
+        if self.step_size <= 0. {
+            return quantity;
+        }
+        (quantity / self.step_size + 1e-9).floor() * self.step_size
+    }
+
+    pub fn round_price(&self, price: f64) -> f64 {
+        if self.tick_size <= 0. {
+            return price;
+        }
+        (price / self.tick_size).round() * self.tick_size
+    }
+
+    pub fn meets_min_notional(&self, quantity: f64, price: f64) -> bool {
+        quantity * price >= self.min_notional
+    }
+}
+
+fn filter_value(filters: &[Value], filter_type: &str, field: &str) -> Option<f64> {
+    let filter = filters.iter().find(|filter| filter["filterType"] == filter_type)?;
+    filter[field].as_str()?.parse().ok()
+}
+
+pub fn parse_exchange_info(body: &str, symbol: &Symbol) -> Result<SymbolFilters> {
+    let exchange_info: Value = serde_json::from_str(body)?;
+    let pair = symbol.pair();
+    let symbols = exchange_info["symbols"].as_array().ok_or(Error::Parse(String::from("No symbols in exchange info")))?;
+    let symbol_info = symbols.iter().find(|info| info["symbol"] == pair.as_str()).ok_or(Error::NoData(format!("No exchange info for {pair}")))?;
+    let filters = symbol_info["filters"].as_array().ok_or(Error::Parse(format!("No filters for {pair}")))?;
+
+    Ok(SymbolFilters {
+        step_size: filter_value(filters, "LOT_SIZE", "stepSize").ok_or(Error::Parse(format!("No LOT_SIZE filter for {pair}")))?,
+        tick_size: filter_value(filters, "PRICE_FILTER", "tickSize").ok_or(Error::Parse(format!("No PRICE_FILTER filter for {pair}")))?,
+        min_notional: filter_value(filters, "NOTIONAL", "minNotional").or_else(|| filter_value(filters, "MIN_NOTIONAL", "minNotional")).unwrap_or_default(),
+    })
+}
+
+pub struct ExchangeInfoCache {
+    base_url: String,
+    filters: HashMap<String, SymbolFilters>,
+}
+
+impl ExchangeInfoCache {
+    pub fn new(base_url: &str) -> Self {
+        Self { base_url: base_url.to_string(), filters: HashMap::new() }
+    }
+
+    pub async fn symbol_filters(&mut self, client: &Client, symbol: &Symbol) -> Result<SymbolFilters> {
+        let pair = symbol.pair();
+        if let Some(filters) = self.filters.get(&pair) {
+            return Ok(*filters);
+        }
+
+        let url = format!("{}/api/v3/exchangeInfo?symbol={pair}", self.base_url);
+        info!("fetching exchange info for {symbol}");
+        let body = client.get(&url).send().await?.error_for_status()?.text().await?;
+        let filters = parse_exchange_info(&body, symbol)?;
+        self.filters.insert(pair, filters);
+        Ok(filters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{build_client, HttpConfig};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const EXCHANGE_INFO: &str = r#"{"symbols":[{"symbol":"ETHUSDT","filters":[
+        {"filterType":"PRICE_FILTER","minPrice":"0.01000000","maxPrice":"1000000.00000000","tickSize":"0.01000000"},
+        {"filterType":"LOT_SIZE","minQty":"0.00010000","maxQty":"9000.00000000","stepSize":"0.00010000"},
+        {"filterType":"NOTIONAL","minNotional":"5.00000000","applyMinToMarket":true}]}]}"#;
+
+    #[tokio::test]
+    async fn test_exchange_info_drives_order_rounding() -> Result<()> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        // Serves a single response, so the second lookup must come from the cache
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{EXCHANGE_INFO}", EXCHANGE_INFO.len());
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let client = build_client(&HttpConfig::default())?;
+        let symbol: Symbol = "ETHUSDT".parse()?;
+        let mut cache = ExchangeInfoCache::new(&format!("http://{address}"));
+        let filters = cache.symbol_filters(&client, &symbol).await?;
+        assert_eq!(cache.symbol_filters(&client, &symbol).await?, filters);
+
+        assert_eq!(filters, SymbolFilters { step_size: 0.0001, tick_size: 0.01, min_notional: 5.0 });
+        assert!((filters.round_quantity(0.123456) - 0.1234).abs() < 1e-12);
+        assert!((filters.round_price(2345.6789) - 2345.68).abs() < 1e-9);
+        assert!(filters.meets_min_notional(0.1234, 2345.68));
+        assert!(!filters.meets_min_notional(0.001, 2345.68));
+
+        Ok(())
+    }
+}
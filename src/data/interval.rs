@@ -0,0 +1,160 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use chrono::Duration;
+use log::warn;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    OneMinute,
+    ThreeMinutes,
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+    TwoHours,
+    FourHours,
+    SixHours,
+    EightHours,
+    TwelveHours,
+    OneDay,
+    ThreeDays,
+    OneWeek,
+}
+
+impl Interval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Interval::OneMinute => "1m",
+            Interval::ThreeMinutes => "3m",
+            Interval::FiveMinutes => "5m",
+            Interval::FifteenMinutes => "15m",
+            Interval::ThirtyMinutes => "30m",
+            Interval::OneHour => "1h",
+            Interval::TwoHours => "2h",
+            Interval::FourHours => "4h",
+            Interval::SixHours => "6h",
+            Interval::EightHours => "8h",
+            Interval::TwelveHours => "12h",
+            Interval::OneDay => "1d",
+            Interval::ThreeDays => "3d",
+            Interval::OneWeek => "1w",
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Interval::OneMinute => Duration::minutes(1),
+            Interval::ThreeMinutes => Duration::minutes(3),
+            Interval::FiveMinutes => Duration::minutes(5),
+            Interval::FifteenMinutes => Duration::minutes(15),
+            Interval::ThirtyMinutes => Duration::minutes(30),
+            Interval::OneHour => Duration::hours(1),
+            Interval::TwoHours => Duration::hours(2),
+            Interval::FourHours => Duration::hours(4),
+            Interval::SixHours => Duration::hours(6),
+            Interval::EightHours => Duration::hours(8),
+            Interval::TwelveHours => Duration::hours(12),
+            Interval::OneDay => Duration::days(1),
+            Interval::ThreeDays => Duration::days(3),
+            Interval::OneWeek => Duration::weeks(1),
+        }
+    }
+
+    /// Candles in a calendar year; crypto trades around the clock, so there is no 252-day trading calendar.
+    pub fn periods_per_year(&self) -> f64 {
+        Duration::days(365).num_seconds() as f64 / self.duration().num_seconds() as f64
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let interval = match s {
+            "1m" => Interval::OneMinute,
+            "3m" => Interval::ThreeMinutes,
+            "5m" => Interval::FiveMinutes,
+            "15m" => Interval::FifteenMinutes,
+            "30m" => Interval::ThirtyMinutes,
+            "1h" => Interval::OneHour,
+            "2h" => Interval::TwoHours,
+            "4h" => Interval::FourHours,
+            "6h" => Interval::SixHours,
+            "8h" => Interval::EightHours,
+            "12h" => Interval::TwelveHours,
+            "1d" => Interval::OneDay,
+            "3d" => Interval::ThreeDays,
+            "1w" => Interval::OneWeek,
+            _ => return Err(Error::Parse(format!("Unsupported interval {s}"))),
+        };
+        Ok(interval)
+    }
+}
+
+pub(crate) fn modal_spacing(klines: &[BinanceKline]) -> Option<Duration> {
+    let mut spacing_counts: HashMap<Duration, usize> = HashMap::new();
+    for pair in klines.windows(2) {
+        *spacing_counts.entry(pair[1].start_time - pair[0].start_time).or_default() += 1;
+    }
+    spacing_counts.into_iter().max_by_key(|(spacing, count)| (*count, *spacing)).map(|(spacing, _)| spacing)
+}
+
+// Returns the modal candle spacing when it differs from the requested interval
+pub fn check_interval_spacing(klines: &[BinanceKline], interval: Interval) -> Option<Duration> {
+    let modal_spacing = modal_spacing(klines)?;
+    if modal_spacing == interval.duration() {
+        return None;
+    }
+
+    warn!("Candle spacing of {} minutes does not match the requested {interval} interval", modal_spacing.num_minutes());
+    Some(modal_spacing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_interval_round_trips_through_str() -> Result<()> {
+        for code in ["1m", "15m", "1h", "4h", "1d", "1w"] {
+            assert_eq!(code.parse::<Interval>()?.to_string(), code);
+        }
+        assert!("1M".parse::<Interval>().is_err());
+        assert_eq!(Interval::FourHours.duration(), Duration::hours(4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_periods_per_year_counts_every_hour() -> Result<()> {
+        assert_eq!(Interval::OneHour.periods_per_year(), 8760.);
+        assert_eq!(Interval::OneDay.periods_per_year(), 365.);
+        assert_eq!(Interval::FifteenMinutes.periods_per_year(), 35040.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_interval_spacing_flags_mismatch() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100., 101., 102., 103., 104.], start_time, Duration::minutes(15));
+
+        assert_eq!(check_interval_spacing(&klines, Interval::OneHour), Some(Duration::minutes(15)));
+        assert_eq!(check_interval_spacing(&klines, Interval::FifteenMinutes), None);
+        assert_eq!(check_interval_spacing(&klines[..1], Interval::OneHour), None);
+
+        Ok(())
+    }
+}
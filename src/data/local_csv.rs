@@ -0,0 +1,137 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use chrono::{DateTime, NaiveDateTime};
+use std::fs;
+use std::path::Path;
+
+/// Zero-based column positions of the kline fields, `None` for columns the file does not have.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColumnMap {
+    pub start_time: Option<usize>,
+    pub open: Option<usize>,
+    pub high: Option<usize>,
+    pub low: Option<usize>,
+    pub close: Option<usize>,
+    /// Optional, rows without it get zero volume
+    pub volume: Option<usize>,
+    pub end_time: Option<usize>,
+}
+
+// Binance's own kline export order
+impl Default for ColumnMap {
+    fn default() -> Self {
+        Self { start_time: Some(0), open: Some(1), high: Some(2), low: Some(3), close: Some(4), volume: Some(5), end_time: Some(6) }
+    }
+}
+
+impl ColumnMap {
+    fn missing_columns(&self) -> Vec<&'static str> {
+        let required = [("start_time", self.start_time), ("open", self.open), ("high", self.high), ("low", self.low), ("close", self.close), ("end_time", self.end_time)];
+        required.iter().filter(|(_, index)| index.is_none()).map(|(name, _)| *name).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CsvSchema {
+    pub delimiter: char,
+    pub column_indices: ColumnMap,
+    pub has_header: bool,
+}
+
+impl Default for CsvSchema {
+    fn default() -> Self {
+        Self { delimiter: ',', column_indices: ColumnMap::default(), has_header: false }
+    }
+}
+
+impl CsvSchema {
+    pub fn validate(&self) -> Result<()> {
+        let missing = self.column_indices.missing_columns();
+        if !missing.is_empty() {
+            return Err(Error::InvalidConfig(format!("CSV schema does not map required columns: {}", missing.join(", "))));
+        }
+        Ok(())
+    }
+}
+
+// Epoch milliseconds as Binance writes them, or a plain `YYYY-MM-DD HH:MM:SS` timestamp
+fn parse_timestamp(value: &str) -> Result<NaiveDateTime> {
+    match value.parse::<i64>() {
+        Ok(millis) => DateTime::from_timestamp_millis(millis).map(|timestamp| timestamp.naive_utc()).ok_or(Error::Parse(format!("Invalid timestamp {value}"))),
+        Err(_) => Ok(NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")?),
+    }
+}
+
+fn field<'a>(fields: &[&'a str], index: Option<usize>, name: &str, line_number: usize) -> Result<&'a str> {
+    let index = index.ok_or(Error::InvalidConfig(format!("Column {name} is not mapped")))?;
+    fields.get(index).map(|value| value.trim()).ok_or(Error::Parse(format!("Line {line_number}: missing {name} in column {index}")))
+}
+
+fn parse_row(line: &str, line_number: usize, schema: &CsvSchema) -> Result<BinanceKline> {
+    let fields: Vec<&str> = line.split(schema.delimiter).collect();
+    let columns = &schema.column_indices;
+    let volume = match columns.volume {
+        Some(_) => field(&fields, columns.volume, "volume", line_number)?.parse()?,
+        None => 0.,
+    };
+    Ok(BinanceKline {
+        start_time: parse_timestamp(field(&fields, columns.start_time, "start_time", line_number)?)?,
+        open: field(&fields, columns.open, "open", line_number)?.parse()?,
+        close: field(&fields, columns.close, "close", line_number)?.parse()?,
+        high: field(&fields, columns.high, "high", line_number)?.parse()?,
+        low: field(&fields, columns.low, "low", line_number)?.parse()?,
+        volume,
+        end_time: parse_timestamp(field(&fields, columns.end_time, "end_time", line_number)?)?,
+    })
+}
+
+/// Loads candles from a local CSV file laid out as described by `schema`.
+pub fn get_kline_data_from_csv(path: &Path, schema: &CsvSchema) -> Result<Vec<BinanceKline>> {
+    schema.validate()?;
+    let content = fs::read_to_string(path)?;
+    let rows = content.lines().enumerate().skip(usize::from(schema.has_header)).filter(|(_, line)| !line.trim().is_empty());
+    let klines = rows.map(|(index, line)| parse_row(line, index + 1, schema)).collect::<Result<Vec<BinanceKline>>>()?;
+    if klines.is_empty() {
+        return Err(Error::NoData(format!("No klines in {}", path.display())));
+    }
+    Ok(klines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use std::io::Write;
+
+    #[test]
+    fn test_semicolon_file_with_reordered_columns() -> Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "close;volume;open_time;high;low;open;close_time")?;
+        writeln!(file, "101.5;12.5;2024-01-01 00:00:00;102;99;100;2024-01-01 00:59:59")?;
+        writeln!(file, "103;7;1704070800000;104;101;101.5;1704074399999")?;
+
+        let column_indices = ColumnMap { start_time: Some(2), open: Some(5), high: Some(3), low: Some(4), close: Some(0), volume: Some(1), end_time: Some(6) };
+        let schema = CsvSchema { delimiter: ';', column_indices, has_header: true };
+        let klines = get_kline_data_from_csv(file.path(), &schema)?;
+
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[0], BinanceKline { start_time, open: 100.0, close: 101.5, high: 102.0, low: 99.0, volume: 12.5, end_time: start_time + Duration::seconds(3599) });
+        assert_eq!(klines[1].start_time, start_time + Duration::hours(1));
+        assert_eq!((klines[1].open, klines[1].close), (101.5, 103.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unmapped_required_column_is_rejected() -> Result<()> {
+        let schema = CsvSchema { column_indices: ColumnMap { close: None, volume: None, ..Default::default() }, ..Default::default() };
+
+        let error = schema.validate().err().ok_or(anyhow!("Expected an invalid schema"))?;
+        assert!(error.to_string().contains("close"));
+        assert!(!error.to_string().contains("volume"));
+
+        Ok(())
+    }
+}
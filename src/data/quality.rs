@@ -0,0 +1,74 @@
+use crate::data::interval::modal_spacing;
+use crate::data::BinanceKline;
+use chrono::NaiveDateTime;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct DataQualityReport {
+    pub candle_count: usize,
+    pub first_start: Option<NaiveDateTime>,
+    pub last_end: Option<NaiveDateTime>,
+    pub gaps: usize,
+    pub zero_volume: usize,
+    pub invariant_violations: usize,
+}
+
+impl fmt::Display for DataQualityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.first_start, self.last_end) {
+            (Some(first_start), Some(last_end)) => write!(f, "{} candles from {first_start} to {last_end}", self.candle_count)?,
+            _ => write!(f, "{} candles", self.candle_count)?,
+        }
+        write!(f, ", {} gaps, {} zero-volume candles, {} OHLC invariant violations", self.gaps, self.zero_volume, self.invariant_violations)
+    }
+}
+
+fn violates_ohlc_invariant(kline: &BinanceKline) -> bool {
+    kline.high < kline.low || kline.close < kline.low || kline.close > kline.high
+}
+
+// Gaps are measured against the most common spacing, so the report does not need the requested interval
+pub fn data_quality_report(klines: &[BinanceKline]) -> DataQualityReport {
+    let gaps = match modal_spacing(klines) {
+        Some(spacing) => klines.windows(2).filter(|pair| pair[1].start_time - pair[0].start_time > spacing).count(),
+        None => 0,
+    };
+
+    DataQualityReport {
+        candle_count: klines.len(),
+        first_start: klines.first().map(|kline| kline.start_time),
+        last_end: klines.last().map(|kline| kline.end_time),
+        gaps,
+        zero_volume: klines.iter().filter(|kline| kline.volume == 0.).count(),
+        invariant_violations: klines.iter().filter(|kline| violates_ohlc_invariant(kline)).count(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_data_quality_report_counts_flaws() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let mut klines = klines_from_closes(&[100.0, 101.0, 102.0, 103.0, 104.0, 105.0], start_time, Duration::hours(1));
+        klines.remove(3);
+        klines[1].volume = 0.;
+        klines[2].high = klines[2].low - 1.;
+
+        let report = data_quality_report(&klines);
+
+        assert_eq!(report.candle_count, 5);
+        assert_eq!(report.first_start, Some(start_time));
+        assert_eq!(report.last_end, Some(start_time + Duration::hours(6) - Duration::seconds(1)));
+        assert_eq!(report.gaps, 1);
+        assert_eq!(report.zero_volume, 1);
+        assert_eq!(report.invariant_violations, 1);
+        assert_eq!(data_quality_report(&[]).candle_count, 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+const KNOWN_QUOTES: [&str; 11] = ["FDUSD", "USDT", "USDC", "TUSD", "BUSD", "BTC", "ETH", "BNB", "EUR", "TRY", "DAI"];
+const STABLE_QUOTES: [&str; 7] = ["FDUSD", "USDT", "USDC", "TUSD", "BUSD", "EUR", "DAI"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub base: String,
+    pub quote: String,
+}
+
+impl Symbol {
+    pub fn pair(&self) -> String {
+        format!("{}{}", self.base, self.quote)
+    }
+
+    pub fn quote_precision(&self) -> u32 {
+        if STABLE_QUOTES.contains(&self.quote.as_str()) {
+            2
+        } else {
+            8
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let pair = s.to_uppercase();
+        // Ambiguous suffixes resolve to the longest known quote
+        let quote =
+            KNOWN_QUOTES.iter().filter(|quote| pair.len() > quote.len() && pair.ends_with(*quote)).max_by_key(|quote| quote.len()).ok_or(Error::Parse(format!("Unknown quote asset in symbol {s}")))?;
+        let base = &pair[..pair.len() - quote.len()];
+        Ok(Self { base: base.to_string(), quote: quote.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_symbols() -> Result<()> {
+        let eth: Symbol = "ETHUSDT".parse()?;
+        assert_eq!(eth, Symbol { base: String::from("ETH"), quote: String::from("USDT") });
+        assert_eq!(eth.to_string(), "ETH/USDT");
+        assert_eq!(eth.pair(), "ETHUSDT");
+        assert_eq!(eth.quote_precision(), 2);
+
+        let sol: Symbol = "solbtc".parse()?;
+        assert_eq!(sol, Symbol { base: String::from("SOL"), quote: String::from("BTC") });
+        assert_eq!(sol.quote_precision(), 8);
+
+        let btc: Symbol = "BTCUSDC".parse()?;
+        assert_eq!(btc, Symbol { base: String::from("BTC"), quote: String::from("USDC") });
+
+        let fdusd: Symbol = "BNBFDUSD".parse()?;
+        assert_eq!(fdusd, Symbol { base: String::from("BNB"), quote: String::from("FDUSD") });
+
+        assert!("USDT".parse::<Symbol>().is_err());
+        assert!("ETHXYZ".parse::<Symbol>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use crate::data::BinanceKline;
+use crate::rng::SplitMix64;
+use chrono::{Duration, NaiveDateTime};
+
+// Deterministic random walk, so benches and tests can run without touching the network
+pub fn generate_klines(count: usize, start_time: NaiveDateTime, interval: Duration, seed: u64) -> Vec<BinanceKline> {
+    let mut rng = SplitMix64::new(seed);
+    let mut price = 1000.0;
+    let mut klines = Vec::with_capacity(count);
+    let mut kline_start = start_time;
+
+    for _ in 0..count {
+        let open = price;
+        let close = open * (1.0 + (rng.next_f64() - 0.5) * 0.02);
+        let high = open.max(close) * (1.0 + rng.next_f64() * 0.005);
+        let low = open.min(close) * (1.0 - rng.next_f64() * 0.005);
+        let volume = 100.0 + rng.next_f64() * 900.0;
+        let end_time = kline_start + interval - Duration::seconds(1);
+
+        klines.push(BinanceKline { start_time: kline_start, open, close, high, low, volume, end_time });
+
+        price = close;
+        kline_start += interval;
+    }
+
+    klines
+}
+
+pub fn klines_from_closes(closes: &[f64], start_time: NaiveDateTime, interval: Duration) -> Vec<BinanceKline> {
+    let mut klines = Vec::with_capacity(closes.len());
+    let mut kline_start = start_time;
+    let mut open = closes.first().copied().unwrap_or_default();
+
+    for &close in closes {
+        let end_time = kline_start + interval - Duration::seconds(1);
+        klines.push(BinanceKline { start_time: kline_start, open, close, high: open.max(close), low: open.min(close), volume: 1.0, end_time });
+        open = close;
+        kline_start += interval;
+    }
+
+    klines
+}
@@ -4,15 +4,95 @@ pub use dca::Dca;
 mod hodl;
 pub use hodl::Hodl;
 
+mod cash;
+pub use cash::Cash;
+
 mod sma;
 pub use sma::SmaPair;
 
 mod sma2;
 pub use sma2::Sma2Pair;
 
-use crate::data::BinanceKline;
+mod ema;
+pub use ema::EmaPair;
+
+mod feature_cache;
+pub use feature_cache::FeatureCache;
+
+mod window;
+pub use window::RollingWindow;
+
+mod crossover;
+pub use crossover::{crossover_action, CrossoverState, SignalMode, TieBreak};
+
+mod atr;
+pub use atr::{true_range, Atr};
+
+mod vortex;
+pub use vortex::VortexConfig;
+
+mod fisher;
+pub use fisher::FisherConfig;
+
+mod dpo;
+pub use dpo::DpoConfig;
+
+mod cadence;
+pub use cadence::Cadence;
+
+mod vwap;
+pub use vwap::VwapConfig;
+
+mod coppock;
+pub use coppock::CoppockConfig;
+
+mod ultimate;
+pub use ultimate::UltimateConfig;
+
+mod linreg;
+pub use linreg::LinRegConfig;
+
+mod elder;
+pub use elder::ElderConfig;
+
+mod ppo;
+pub use ppo::PpoConfig;
+
+mod bollinger;
+pub use bollinger::BollingerConfig;
+
+mod keltner;
+pub use keltner::KeltnerConfig;
+
+mod squeeze;
+pub use squeeze::SqueezeConfig;
+
+mod chandelier;
+pub use chandelier::{ChandelierConfig, ChandelierInstance};
+
+mod wma;
+pub use wma::{Wma, WmaConfig};
+
+mod random;
+pub use random::RandomConfig;
+
+mod ribbon;
+pub use ribbon::RibbonConfig;
+
+mod rsi;
+pub use rsi::RsiConfig;
+
+use crate::data::{BinanceKline, TimestampConvention};
 use yata::core::IndicatorResult;
 
 pub trait BinanceIndicatorInstance {
     fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult;
+
+    /// Number of candles consumed before the indicator can emit a meaningful signal
+    fn warm_up_period(&self) -> usize {
+        0
+    }
+
+    /// Candle end used by calendar based signals, a no-op for indicators that ignore time
+    fn set_timestamp_convention(&mut self, _timestamp_convention: TimestampConvention) {}
 }
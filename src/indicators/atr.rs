@@ -0,0 +1,77 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+pub fn true_range<T: OHLCV>(candle: &T, prev_close: f64) -> f64 {
+    let high_low = candle.high() - candle.low();
+    let high_close = (candle.high() - prev_close).abs();
+    let low_close = (candle.low() - prev_close).abs();
+    high_low.max(high_close).max(low_close)
+}
+
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ATRInstance {
+    cfg: Atr,
+    prev_close: f64,
+    true_ranges: RollingWindow,
+}
+
+impl ATRInstance {
+    pub fn value(&self) -> Option<f64> {
+        self.true_ranges.mean()
+    }
+}
+
+impl IndicatorConfig for Atr {
+    type Instance = ATRInstance;
+
+    const NAME: &'static str = "ATR";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("ATR period must be positive")));
+        }
+        Ok(Self::Instance { true_ranges: RollingWindow::new(self.period), prev_close: candle.close(), cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (1, 0)
+    }
+}
+
+impl IndicatorInstance for ATRInstance {
+    type Config = Atr;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.true_ranges.push(true_range(candle, self.prev_close));
+        self.prev_close = candle.close();
+        IndicatorResult::new(&[self.value().unwrap_or_default()], &[])
+    }
+}
+
+impl BinanceIndicatorInstance for ATRInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
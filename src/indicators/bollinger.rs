@@ -0,0 +1,83 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct BollingerConfig {
+    pub period: usize,
+    pub stddev_mult: f64,
+}
+
+impl Default for BollingerConfig {
+    fn default() -> Self {
+        Self { period: 20, stddev_mult: 2.0 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BollingerInstance {
+    cfg: BollingerConfig,
+    closes: RollingWindow,
+}
+
+impl BollingerInstance {
+    /// (lower, middle, upper) once the window is full
+    pub fn bands(&self) -> Option<(f64, f64, f64)> {
+        if !self.closes.is_full() {
+            return None;
+        }
+        let middle = self.closes.mean()?;
+        let variance = self.closes.iter().map(|close| (close - middle).powi(2)).sum::<f64>() / self.closes.len() as f64;
+        let width = self.cfg.stddev_mult * variance.sqrt();
+        Some((middle - width, middle, middle + width))
+    }
+}
+
+impl IndicatorConfig for BollingerConfig {
+    type Instance = BollingerInstance;
+
+    const NAME: &'static str = "Bollinger";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Bollinger period and stddev_mult must be positive")));
+        }
+        Ok(Self::Instance { closes: RollingWindow::new(self.period), cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0 && self.stddev_mult > 0.
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (3, 0)
+    }
+}
+
+impl IndicatorInstance for BollingerInstance {
+    type Config = BollingerConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.closes.push(candle.close());
+        match self.bands() {
+            Some((lower, middle, upper)) => IndicatorResult::new(&[lower, middle, upper], &[]),
+            None => IndicatorResult::new(&[], &[]),
+        }
+    }
+}
+
+impl BinanceIndicatorInstance for BollingerInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.period
+    }
+}
@@ -0,0 +1,63 @@
+use crate::data::BinanceKline;
+use crate::indicators::BinanceIndicatorInstance;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Cadence {
+    every: usize,
+}
+
+impl Cadence {
+    pub fn new(every: usize) -> Self {
+        Self { every }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CadenceInstance {
+    cfg: Cadence,
+    candles_seen: usize,
+}
+
+impl IndicatorConfig for Cadence {
+    type Instance = CadenceInstance;
+
+    const NAME: &'static str = "Cadence";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Cadence must be positive")));
+        }
+        Ok(Self::Instance { cfg: self, candles_seen: 0 })
+    }
+    fn validate(&self) -> bool {
+        self.every > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (0, 1)
+    }
+}
+
+impl IndicatorInstance for CadenceInstance {
+    type Config = Cadence;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, _candle: &T) -> IndicatorResult {
+        let action = if self.candles_seen.is_multiple_of(self.cfg.every) { Action::Buy(1) } else { Action::None };
+        self.candles_seen += 1;
+        IndicatorResult::new(&[], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for CadenceInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::data::BinanceKline;
+use crate::indicators::atr::ATRInstance;
+use crate::indicators::{Atr, BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct ChandelierConfig {
+    pub period: usize,
+    pub atr_mult: f64,
+}
+
+impl Default for ChandelierConfig {
+    fn default() -> Self {
+        Self { period: 22, atr_mult: 3.0 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChandelierInstance {
+    cfg: ChandelierConfig,
+    highs: RollingWindow,
+    lows: RollingWindow,
+    atr: ATRInstance,
+}
+
+impl ChandelierInstance {
+    /// Long exit line, the highest high over the period minus a multiple of ATR
+    pub fn line(&self) -> Option<f64> {
+        if !self.highs.is_full() {
+            return None;
+        }
+        Some(self.highs.max()? - self.cfg.atr_mult * self.atr.value()?)
+    }
+
+    /// Short exit line, the lowest low over the period plus a multiple of ATR
+    pub fn short_line(&self) -> Option<f64> {
+        if !self.lows.is_full() {
+            return None;
+        }
+        Some(self.lows.min()? + self.cfg.atr_mult * self.atr.value()?)
+    }
+}
+
+impl IndicatorConfig for ChandelierConfig {
+    type Instance = ChandelierInstance;
+
+    const NAME: &'static str = "Chandelier";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Chandelier period and atr_mult must be positive")));
+        }
+        Ok(Self::Instance { highs: RollingWindow::new(self.period), lows: RollingWindow::new(self.period), atr: Atr::new(self.period).init(candle)?, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0 && self.atr_mult > 0.
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 2)
+    }
+}
+
+impl IndicatorInstance for ChandelierInstance {
+    type Config = ChandelierConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    // First signal exits a long with a sell, the second exits a short with a buy
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.highs.push(candle.high());
+        self.lows.push(candle.low());
+        self.atr.next(candle);
+        match (self.line(), self.short_line()) {
+            (Some(line), Some(short_line)) => {
+                let long_exit = if candle.close() < line { Action::Sell(1) } else { Action::None };
+                let short_exit = if candle.close() > short_line { Action::Buy(1) } else { Action::None };
+                IndicatorResult::new(&[line, short_line], &[long_exit, short_exit])
+            }
+            _ => IndicatorResult::new(&[], &[Action::None, Action::None]),
+        }
+    }
+}
+
+impl BinanceIndicatorInstance for ChandelierInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.period
+    }
+}
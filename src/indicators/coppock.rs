@@ -0,0 +1,133 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use std::collections::VecDeque;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct CoppockConfig {
+    pub roc1: usize,
+    pub roc2: usize,
+    pub wma: usize,
+}
+
+impl Default for CoppockConfig {
+    fn default() -> Self {
+        Self { roc1: 14, roc2: 11, wma: 10 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CoppockInstance {
+    cfg: CoppockConfig,
+    closes: VecDeque<f64>,
+    roc_sums: RollingWindow,
+    last_value: Option<f64>,
+    prev_value: Option<f64>,
+}
+
+impl CoppockInstance {
+    fn rate_of_change(&self, period: usize) -> Option<f64> {
+        let current = *self.closes.back()?;
+        let past = *self.closes.get(self.closes.len().checked_sub(period + 1)?)?;
+        if past == 0. {
+            return None;
+        }
+        Some(100. * (current - past) / past)
+    }
+
+    fn weighted_average(&self) -> f64 {
+        let weighted_sum: f64 = self.roc_sums.iter().enumerate().map(|(i, value)| (i + 1) as f64 * value).sum();
+        let weights = (self.roc_sums.len() * (self.roc_sums.len() + 1)) as f64 / 2.;
+        weighted_sum / weights
+    }
+}
+
+impl IndicatorConfig for CoppockConfig {
+    type Instance = CoppockInstance;
+
+    const NAME: &'static str = "Coppock";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Coppock periods must be positive")));
+        }
+        Ok(Self::Instance { closes: VecDeque::with_capacity(self.roc1.max(self.roc2) + 2), roc_sums: RollingWindow::new(self.wma), last_value: None, prev_value: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.roc1 > 0 && self.roc2 > 0 && self.wma > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (1, 1)
+    }
+}
+
+impl IndicatorInstance for CoppockInstance {
+    type Config = CoppockConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.closes.push_back(candle.close());
+        if self.closes.len() > self.cfg.roc1.max(self.cfg.roc2) + 1 {
+            self.closes.pop_front();
+        }
+
+        let (Some(roc1), Some(roc2)) = (self.rate_of_change(self.cfg.roc1), self.rate_of_change(self.cfg.roc2)) else {
+            return IndicatorResult::new(&[], &[Action::None]);
+        };
+        self.roc_sums.push(roc1 + roc2);
+        if !self.roc_sums.is_full() {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let value = self.weighted_average();
+        let action = match (self.prev_value, self.last_value) {
+            (Some(prev_value), Some(last_value)) if last_value < 0. && prev_value > last_value && value > last_value => Action::Buy(1),
+            _ => Action::None,
+        };
+        self.prev_value = self.last_value;
+        self.last_value = Some(value);
+
+        IndicatorResult::new(&[value], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for CoppockInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_coppock_buys_when_turning_up_below_zero() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2018, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let mut closes = vec![200.0; 24];
+        closes.extend((1..=24).map(|i| 200.0 - 100.0 * i as f64 / 24.0));
+        closes.extend((1..=24).map(|i| 100.0 + 3.0 * i as f64));
+        let klines = klines_from_closes(&closes, start_time, Duration::days(30));
+
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut coppock = CoppockConfig::default().init(first_kline)?;
+        let results: Vec<IndicatorResult> = klines.iter().map(|kline| coppock.next_binance_kline(kline)).collect();
+
+        let buys: Vec<usize> = results.iter().enumerate().filter(|(_, result)| result.signals()[0] == Action::Buy(1)).map(|(i, _)| i).collect();
+        assert_eq!(buys, vec![48]);
+        assert!(results[48].values()[0] < 0.);
+        assert!(results[47].values()[0] < results[46].values()[0]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use yata::core::Action;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TieBreak {
+    /// Repeat the last crossover action when both averages are equal
+    HoldPrevious,
+    /// Do nothing when both averages are equal
+    #[default]
+    None,
+    /// Treat equal averages as a bullish crossover
+    TreatAsBull,
+}
+
+pub fn crossover_action<T: PartialOrd>(short: T, long: T, tie_break: TieBreak, previous: Action) -> Action {
+    if short > long {
+        Action::Buy(1)
+    } else if short < long {
+        Action::Sell(1)
+    } else if short == long {
+        match tie_break {
+            TieBreak::HoldPrevious => previous,
+            TieBreak::None => Action::None,
+            TieBreak::TreatAsBull => Action::Buy(1),
+        }
+    } else {
+        Action::None
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SignalMode {
+    /// Act on the candle where the short average moves to the other side of the long one
+    #[default]
+    Crossover,
+    /// Act on the relationship of both averages at the first candle of each month
+    Monthly,
+}
+
+/// Remembers which side of the long average the short one was last seen on
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CrossoverState {
+    last_side: Option<Action>,
+}
+
+impl CrossoverState {
+    /// Returns the new side when it flips, level averages keep the last side and the first side seen only primes the state
+    pub fn flip(&mut self, side: Action) -> Action {
+        if side == Action::None {
+            return Action::None;
+        }
+        let action = match self.last_side {
+            Some(last_side) if last_side != side => side,
+            _ => Action::None,
+        };
+        self.last_side = Some(side);
+        action
+    }
+}
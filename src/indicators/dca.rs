@@ -1,4 +1,4 @@
-use crate::data::BinanceKline;
+use crate::data::{BinanceKline, TimestampConvention};
 use crate::indicators::BinanceIndicatorInstance;
 use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use yata::core::{Action, Error, IndicatorResult, OHLCV};
@@ -11,6 +11,7 @@ pub struct Dca;
 pub struct DCAInstance {
     cfg: Dca,
     last_timestamp: NaiveDateTime,
+    timestamp_convention: TimestampConvention,
 }
 
 impl IndicatorConfig for Dca {
@@ -21,6 +22,7 @@ impl IndicatorConfig for Dca {
     fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
         Ok(Self::Instance {
             last_timestamp: NaiveDate::from_ymd_opt(2000, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(Error::Other(String::from("Could not create last_timestamp")))?, // FIXME: a magic date before crypto happens
+            timestamp_convention: TimestampConvention::default(),
             cfg: self,
         })
     }
@@ -49,11 +51,15 @@ impl IndicatorInstance for DCAInstance {
 
 impl BinanceIndicatorInstance for DCAInstance {
     fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
-        let current_time = candle.start_time;
+        let current_time = candle.timestamp(self.timestamp_convention);
         let current_month = current_time.month();
         let last_month = self.last_timestamp.month();
         let action = if current_month == last_month { Action::None } else { Action::Buy(1) };
         self.last_timestamp = current_time;
         IndicatorResult::new(&[], &[action])
     }
+
+    fn set_timestamp_convention(&mut self, timestamp_convention: TimestampConvention) {
+        self.timestamp_convention = timestamp_convention;
+    }
 }
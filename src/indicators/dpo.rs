@@ -0,0 +1,134 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use std::collections::VecDeque;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct DpoConfig {
+    pub period: usize,
+    pub threshold: f64,
+}
+
+impl DpoConfig {
+    fn displacement(&self) -> usize {
+        self.period / 2 + 1
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DPOInstance {
+    cfg: DpoConfig,
+    closes: RollingWindow,
+    past_averages: VecDeque<f64>,
+}
+
+impl IndicatorConfig for DpoConfig {
+    type Instance = DPOInstance;
+
+    const NAME: &'static str = "DPO";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("DPO period must be positive and threshold non-negative")));
+        }
+        Ok(Self::Instance { closes: RollingWindow::new(self.period), past_averages: VecDeque::with_capacity(self.displacement() + 1), cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0 && self.threshold >= 0.
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (1, 1)
+    }
+}
+
+impl IndicatorInstance for DPOInstance {
+    type Config = DpoConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.closes.push(candle.close());
+        if !self.closes.is_full() {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        if let Some(average) = self.closes.mean() {
+            self.past_averages.push_back(average);
+        }
+        if self.past_averages.len() <= self.cfg.displacement() {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let displaced_average = self.past_averages.pop_front().unwrap_or_default();
+        let dpo = candle.close() - displaced_average;
+
+        let action = if dpo > self.cfg.threshold {
+            Action::Sell(1)
+        } else if dpo < -self.cfg.threshold {
+            Action::Buy(1)
+        } else {
+            Action::None
+        };
+
+        IndicatorResult::new(&[dpo], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for DPOInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.period + self.cfg.displacement()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_dpo_oscillates_around_zero_on_cycle() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (0..210).map(|i| 100.0 + 10.0 * (2.0 * PI * i as f64 / 20.0).sin()).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+
+        let config = DpoConfig { period: 20, threshold: 5.0 };
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut dpo = config.init(first_kline)?;
+
+        let mut values = Vec::new();
+        for kline in &klines {
+            let result = dpo.next_binance_kline(kline);
+            if let Some(value) = result.values().first() {
+                let expected = if *value > 5.0 {
+                    Action::Sell(1)
+                } else if *value < -5.0 {
+                    Action::Buy(1)
+                } else {
+                    Action::None
+                };
+                assert_eq!(result.signals()[0], expected);
+                values.push(*value);
+            }
+        }
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!(mean.abs() < 0.5);
+        assert!(values.iter().any(|value| *value > 5.0));
+        assert!(values.iter().any(|value| *value < -5.0));
+
+        Ok(())
+    }
+}
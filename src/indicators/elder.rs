@@ -0,0 +1,126 @@
+use crate::data::BinanceKline;
+use crate::indicators::BinanceIndicatorInstance;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::methods::EMA;
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct ElderConfig {
+    pub ema_period: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct ElderInstance {
+    cfg: ElderConfig,
+    ema: EMA,
+    candles_seen: usize,
+    prev_power: Option<(f64, f64)>,
+}
+
+impl IndicatorConfig for ElderConfig {
+    type Instance = ElderInstance;
+
+    const NAME: &'static str = "Elder";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Elder EMA period must be positive")));
+        }
+        Ok(Self::Instance { ema: EMA::new(self.ema_period, &candle.close())?, candles_seen: 0, prev_power: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.ema_period > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 1)
+    }
+}
+
+impl IndicatorInstance for ElderInstance {
+    type Config = ElderConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let ema = self.ema.next(&candle.close());
+        self.candles_seen += 1;
+        if self.candles_seen < self.cfg.ema_period as usize {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let bull_power = candle.high() - ema;
+        let bear_power = candle.low() - ema;
+
+        // Long only when Bull Power is rising and Bear Power is negative but rising
+        let action = match self.prev_power {
+            Some((prev_bull, prev_bear)) if bull_power > prev_bull && bear_power < 0. && bear_power > prev_bear => Action::Buy(1),
+            _ => Action::None,
+        };
+        self.prev_power = Some((bull_power, bear_power));
+
+        IndicatorResult::new(&[bull_power, bear_power], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for ElderInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.ema_period as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_elder_ray_confirms_uptrend() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let flat = (0..20).map(|_| 100.0);
+        let rising = (1..=80).map(|i| 100.0 + 0.2 * i as f64);
+        let falling = (1..=10).map(|i| 116.0 - 0.5 * i as f64);
+        let closes: Vec<f64> = flat.chain(rising).chain(falling).collect();
+        let mut klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+        for kline in klines.iter_mut() {
+            kline.high = kline.close + 1.0;
+            kline.low = kline.close - 1.0;
+        }
+
+        let config = ElderConfig { ema_period: 10 };
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut elder = config.init(first_kline)?;
+
+        for (i, kline) in klines.iter().enumerate() {
+            let result = elder.next_binance_kline(kline);
+            let Some(&[bull_power, bear_power]) = result.values().get(..2) else {
+                assert!(i < 9);
+                continue;
+            };
+            match i {
+                0..=19 => {
+                    assert_eq!((bull_power, bear_power), (1.0, -1.0));
+                    assert_eq!(result.signals()[0], Action::None);
+                }
+                20..=99 => {
+                    assert!(bull_power > 1.0);
+                    assert!(bear_power < 0.0 && bear_power > -1.0);
+                    assert_eq!(result.signals()[0], Action::Buy(1));
+                }
+                _ => assert_eq!(result.signals()[0], Action::None),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use crate::data::BinanceKline;
+use crate::indicators::{crossover_action, BinanceIndicatorInstance, CrossoverState, TieBreak};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::methods::EMA;
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct EmaPair {
+    short_window: u8,
+    long_window: u8,
+    tie_break: TieBreak,
+}
+
+impl EmaPair {
+    pub fn new(short_window: u8, long_window: u8) -> Self {
+        Self { short_window, long_window, tie_break: TieBreak::default() }
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EMAInstance {
+    cfg: EmaPair,
+    ema1: EMA,
+    ema2: EMA,
+    candles_seen: usize,
+    crossover: CrossoverState,
+    last_signal: Action,
+}
+
+impl IndicatorConfig for EmaPair {
+    type Instance = EMAInstance;
+
+    const NAME: &'static str = "EMA";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("EMA windows must be positive and short shorter than long")));
+        }
+        Ok(Self::Instance {
+            ema1: EMA::new(self.short_window, &candle.close())?,
+            ema2: EMA::new(self.long_window, &candle.close())?,
+            candles_seen: 0,
+            crossover: CrossoverState::default(),
+            last_signal: Action::None,
+            cfg: self,
+        })
+    }
+    fn validate(&self) -> bool {
+        self.short_window > 0 && self.short_window < self.long_window
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 1)
+    }
+}
+
+impl IndicatorInstance for EMAInstance {
+    type Config = EmaPair;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let short_ma = self.ema1.next(&candle.close());
+        let long_ma = self.ema2.next(&candle.close());
+        self.candles_seen += 1;
+        if self.candles_seen < self.cfg.long_window as usize {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let side = crossover_action(short_ma, long_ma, self.cfg.tie_break, self.last_signal);
+        let action = self.crossover.flip(side);
+        if action != Action::None {
+            self.last_signal = action;
+        }
+
+        IndicatorResult::new(&[short_ma, long_ma], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for EMAInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.long_window as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_signals_only_on_crossovers() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (0..20).map(|i| 100.0 - f64::from(i)).chain((1..=20).map(|i| 80.0 + 2.0 * f64::from(i))).chain((1..=20).map(|i| 120.0 - 2.0 * f64::from(i))).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut ema = EmaPair::new(3, 6).init(first_kline)?;
+        let crossovers: Vec<(usize, Action)> = klines.iter().enumerate().map(|(i, kline)| (i, ema.next_binance_kline(kline).signals()[0])).filter(|(_, action)| *action != Action::None).collect();
+
+        assert_eq!(crossovers, vec![(22, Action::Buy(1)), (43, Action::Sell(1))]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tie_break_applies_to_level_averages() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = [100.0; 8].into_iter().chain((1..=4).map(|i| 100.0 - 10.0 * f64::from(i))).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+        let signals = |tie_break: TieBreak| -> Result<Vec<Action>> {
+            let mut ema = EmaPair::new(3, 6).with_tie_break(tie_break).init(&klines[0])?;
+            Ok(klines.iter().map(|kline| ema.next_binance_kline(kline).signals()[0]).filter(|action| *action != Action::None).collect())
+        };
+
+        // Level averages only count as a side when the tie break says so, the fall then crosses from it
+        assert_eq!(signals(TieBreak::TreatAsBull)?, vec![Action::Sell(1)]);
+        assert!(signals(TieBreak::None)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_unordered_windows() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0], start_time, Duration::hours(1));
+
+        assert!(EmaPair::new(26, 12).init(&klines[0]).is_err());
+        assert!(EmaPair::new(0, 12).init(&klines[0]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::data::BinanceKline;
+use crate::indicators::sma2::Sma2;
+use std::collections::HashMap;
+
+/// Moving averages of a dataset precomputed once per period and shared read-only, e.g. behind an `Arc`,
+/// by every strategy evaluation of a parameter sweep over the same klines.
+#[derive(Debug, Default)]
+pub struct FeatureCache {
+    sma: HashMap<usize, Vec<Option<f64>>>,
+}
+
+impl FeatureCache {
+    // Seeded with the first close exactly like an `Sma2Pair` initialised on the first kline
+    pub fn new(klines: &[BinanceKline], periods: &[usize]) -> Self {
+        let first_close = klines.first().map(|kline| kline.close).unwrap_or_default();
+        let sma = periods
+            .iter()
+            .map(|&period| {
+                let mut average = Sma2::new(period, first_close, None);
+                (period, klines.iter().map(|kline| average.update_price(kline.close)).collect())
+            })
+            .collect();
+        Self { sma }
+    }
+
+    pub fn has_sma(&self, period: usize) -> bool {
+        self.sma.contains_key(&period)
+    }
+
+    /// Moving average of `period` closes at candle `index`, `None` during warm-up or past the cached data
+    pub fn sma(&self, period: usize, index: usize) -> Option<f64> {
+        self.sma.get(&period)?.get(index).copied().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::generate_klines;
+    use crate::indicators::{BinanceIndicatorInstance, Sma2Pair};
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use std::sync::Arc;
+    use std::thread;
+    use yata::core::Action;
+    use yata::prelude::*;
+
+    fn signals(sma_pair: Sma2Pair, klines: &[BinanceKline]) -> Result<Vec<Action>> {
+        let mut indicator = sma_pair.init(&klines[0])?;
+        Ok(klines.iter().map(|kline| indicator.next_binance_kline(kline).signals()[0]).collect())
+    }
+
+    #[test]
+    fn test_cached_grid_search_matches_uncached() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(500, start_time, Duration::days(1), 21);
+        let grid: Vec<(usize, usize)> = [2, 3, 5].iter().flat_map(|&short| [8, 13, 21].map(|long| (short, long))).collect();
+
+        let uncached = grid.iter().map(|&(short, long)| signals(Sma2Pair::new(short, long), &klines)).collect::<Result<Vec<_>>>()?;
+
+        let cache = Arc::new(FeatureCache::new(&klines, &[2, 3, 5, 8, 13, 21]));
+        let cached = thread::scope(|scope| {
+            let handles: Vec<_> = grid
+                .iter()
+                .map(|&(short, long)| {
+                    let (cache, klines) = (Arc::clone(&cache), &klines);
+                    scope.spawn(move || signals(Sma2Pair::new(short, long).with_cache(cache), klines))
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().map_err(|_| anyhow!("Grid search thread panicked"))?).collect::<Result<Vec<_>>>()
+        })?;
+
+        assert_eq!(cached, uncached);
+        assert!(uncached.iter().all(|actions| actions.iter().any(|action| *action != Action::None)));
+        assert!(Sma2Pair::new(2, 34).with_cache(Arc::clone(&cache)).init(&klines[0]).is_err());
+        assert!(Sma2Pair::new(2, 8).with_cache(cache).with_history(&[100.0; 8]).init(&klines[0]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+const MAX_NORMALISED: f64 = 0.999;
+
+fn fisher_transform(value: f64) -> f64 {
+    let value = value.clamp(-MAX_NORMALISED, MAX_NORMALISED);
+    0.5 * ((1. + value) / (1. - value)).ln()
+}
+
+#[derive(Debug, Clone)]
+pub struct FisherConfig {
+    pub period: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct FisherInstance {
+    cfg: FisherConfig,
+    mid_prices: RollingWindow,
+    normalised: f64,
+    fisher: f64,
+    trigger: f64,
+}
+
+impl IndicatorConfig for FisherConfig {
+    type Instance = FisherInstance;
+
+    const NAME: &'static str = "Fisher";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Fisher period must be positive")));
+        }
+        Ok(Self::Instance { mid_prices: RollingWindow::new(self.period), normalised: 0., fisher: 0., trigger: 0., cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 1)
+    }
+}
+
+impl IndicatorInstance for FisherInstance {
+    type Config = FisherConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let mid_price = (candle.high() + candle.low()) / 2.;
+        self.mid_prices.push(mid_price);
+
+        let highest = self.mid_prices.max().unwrap_or(mid_price);
+        let lowest = self.mid_prices.min().unwrap_or(mid_price);
+        let position = if highest > lowest { (mid_price - lowest) / (highest - lowest) } else { 0.5 };
+
+        // Ehlers' smoothing keeps the normalised price inside [-1, 1]; the clamp in fisher_transform stops it touching the bounds
+        self.normalised = (0.66 * (position - 0.5) + 0.67 * self.normalised).clamp(-MAX_NORMALISED, MAX_NORMALISED);
+        let prev_fisher = self.fisher;
+        let prev_trigger = self.trigger;
+        self.trigger = prev_fisher;
+        self.fisher = fisher_transform(self.normalised) + 0.5 * prev_fisher;
+
+        if !self.mid_prices.is_full() {
+            return IndicatorResult::new(&[self.fisher, self.trigger], &[Action::None]);
+        }
+
+        let action = if prev_fisher <= prev_trigger && self.fisher > self.trigger {
+            Action::Buy(1)
+        } else if prev_fisher >= prev_trigger && self.fisher < self.trigger {
+            Action::Sell(1)
+        } else {
+            Action::None
+        };
+
+        IndicatorResult::new(&[self.fisher, self.trigger], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for FisherInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))
+    }
+
+    fn create_kline(price: f64) -> Result<BinanceKline> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        Ok(BinanceKline { start_time: timestamp, open: price, close: price, high: price, low: price, volume: 1.0, end_time: timestamp })
+    }
+
+    #[test]
+    fn test_fisher_transform_is_clamped() {
+        assert!(fisher_transform(1.0).is_finite());
+        assert!(fisher_transform(-1.0).is_finite());
+        assert_eq!(fisher_transform(1.0), fisher_transform(MAX_NORMALISED));
+    }
+
+    #[test]
+    fn test_fisher_stays_finite_on_extreme_price() -> Result<()> {
+        let first_kline = create_kline(100.0)?;
+        let mut fisher = FisherConfig { period: 5 }.init(&first_kline)?;
+        for _ in 0..5 {
+            fisher.next_binance_kline(&first_kline);
+        }
+
+        for _ in 0..50 {
+            let result = fisher.next_binance_kline(&create_kline(1e300)?);
+            assert!(result.values().iter().all(|value| value.is_finite()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::data::BinanceKline;
+use crate::indicators::atr::ATRInstance;
+use crate::indicators::{Atr, BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct KeltnerConfig {
+    pub period: usize,
+    pub atr_mult: f64,
+}
+
+impl Default for KeltnerConfig {
+    fn default() -> Self {
+        Self { period: 20, atr_mult: 1.5 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeltnerInstance {
+    cfg: KeltnerConfig,
+    closes: RollingWindow,
+    atr: ATRInstance,
+}
+
+impl KeltnerInstance {
+    /// (lower, middle, upper) once the window is full
+    pub fn bands(&self) -> Option<(f64, f64, f64)> {
+        if !self.closes.is_full() {
+            return None;
+        }
+        let middle = self.closes.mean()?;
+        let width = self.cfg.atr_mult * self.atr.value()?;
+        Some((middle - width, middle, middle + width))
+    }
+}
+
+impl IndicatorConfig for KeltnerConfig {
+    type Instance = KeltnerInstance;
+
+    const NAME: &'static str = "Keltner";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Keltner period and atr_mult must be positive")));
+        }
+        Ok(Self::Instance { closes: RollingWindow::new(self.period), atr: Atr::new(self.period).init(candle)?, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0 && self.atr_mult > 0.
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (3, 0)
+    }
+}
+
+impl IndicatorInstance for KeltnerInstance {
+    type Config = KeltnerConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.closes.push(candle.close());
+        self.atr.next(candle);
+        match self.bands() {
+            Some((lower, middle, upper)) => IndicatorResult::new(&[lower, middle, upper], &[]),
+            None => IndicatorResult::new(&[], &[]),
+        }
+    }
+}
+
+impl BinanceIndicatorInstance for KeltnerInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.period
+    }
+}
@@ -0,0 +1,144 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct LinRegConfig {
+    pub period: usize,
+    pub stddev_mult: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct LinRegInstance {
+    cfg: LinRegConfig,
+    closes: RollingWindow,
+}
+
+impl IndicatorConfig for LinRegConfig {
+    type Instance = LinRegInstance;
+
+    const NAME: &'static str = "LinReg";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("LinReg period must be at least 2 and stddev_mult positive")));
+        }
+        Ok(Self::Instance { closes: RollingWindow::new(self.period), cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period >= 2 && self.stddev_mult > 0.
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (4, 2)
+    }
+}
+
+impl LinRegInstance {
+    // Least-squares fit over the window with x = 0..period, returns (slope, fitted value at the last candle, residual stddev)
+    fn fit(&self) -> Option<(f64, f64, f64)> {
+        let n = self.closes.len() as f64;
+        let mean_x = (n - 1.) / 2.;
+        let mean_y = self.closes.mean()?;
+
+        let (covariance, variance) = self.closes.iter().enumerate().fold((0., 0.), |(covariance, variance), (x, y)| {
+            let dx = x as f64 - mean_x;
+            (covariance + dx * (y - mean_y), variance + dx * dx)
+        });
+        let slope = covariance / variance;
+        let intercept = mean_y - slope * mean_x;
+
+        let squared_residuals: f64 = self.closes.iter().enumerate().map(|(x, y)| (y - (intercept + slope * x as f64)).powi(2)).sum();
+        let stddev = (squared_residuals / n).sqrt();
+
+        Some((slope, intercept + slope * (n - 1.), stddev))
+    }
+}
+
+impl IndicatorInstance for LinRegInstance {
+    type Config = LinRegConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.closes.push(candle.close());
+        if !self.closes.is_full() {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let Some((slope, middle, stddev)) = self.fit() else {
+            return IndicatorResult::new(&[], &[Action::None]);
+        };
+        let upper = middle + self.cfg.stddev_mult * stddev;
+        let lower = middle - self.cfg.stddev_mult * stddev;
+
+        let reversion = if candle.close() >= upper {
+            Action::Sell(1)
+        } else if candle.close() <= lower {
+            Action::Buy(1)
+        } else {
+            Action::None
+        };
+        let trend = if slope > 0. {
+            Action::Buy(1)
+        } else if slope < 0. {
+            Action::Sell(1)
+        } else {
+            Action::None
+        };
+
+        IndicatorResult::new(&[slope, middle, upper, lower], &[reversion, trend])
+    }
+}
+
+impl BinanceIndicatorInstance for LinRegInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use crate::rng::SplitMix64;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_linreg_noisy_uptrend() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let mut rng = SplitMix64::new(7);
+        let closes: Vec<f64> = (0..200).map(|i| 100.0 + i as f64 + 2.0 * rng.next_gaussian()).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+
+        let config = LinRegConfig { period: 20, stddev_mult: 1.0 };
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut linreg = config.init(first_kline)?;
+
+        let mut reversions = Vec::new();
+        for kline in &klines {
+            let result = linreg.next_binance_kline(kline);
+            if let Some(slope) = result.values().first() {
+                assert!(*slope > 0.);
+                assert_eq!(result.signals()[1], Action::Buy(1));
+                reversions.push(result.signals()[0]);
+            }
+        }
+
+        assert_eq!(reversions.len(), 181);
+        assert!(reversions.contains(&Action::Sell(1)));
+        assert!(reversions.contains(&Action::Buy(1)));
+
+        Ok(())
+    }
+}
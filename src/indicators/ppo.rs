@@ -0,0 +1,162 @@
+use crate::data::BinanceKline;
+use crate::indicators::BinanceIndicatorInstance;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::methods::EMA;
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct PpoConfig {
+    pub fast: u8,
+    pub slow: u8,
+    pub signal: u8,
+}
+
+impl Default for PpoConfig {
+    fn default() -> Self {
+        Self { fast: 12, slow: 26, signal: 9 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PPOInstance {
+    cfg: PpoConfig,
+    fast_ema: EMA,
+    slow_ema: EMA,
+    signal_ema: Option<EMA>,
+    candles_seen: usize,
+    prev_histogram: Option<f64>,
+}
+
+impl IndicatorConfig for PpoConfig {
+    type Instance = PPOInstance;
+
+    const NAME: &'static str = "PPO";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("PPO periods must be positive and fast shorter than slow")));
+        }
+        Ok(Self::Instance { fast_ema: EMA::new(self.fast, &candle.close())?, slow_ema: EMA::new(self.slow, &candle.close())?, signal_ema: None, candles_seen: 0, prev_histogram: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.fast > 0 && self.signal > 0 && self.fast < self.slow
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (3, 1)
+    }
+}
+
+impl IndicatorInstance for PPOInstance {
+    type Config = PpoConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let fast = self.fast_ema.next(&candle.close());
+        let slow = self.slow_ema.next(&candle.close());
+        self.candles_seen += 1;
+        if self.candles_seen < self.cfg.slow as usize || slow == 0. {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let ppo = 100. * (fast - slow) / slow;
+        let signal = match self.signal_ema.as_mut() {
+            Some(signal_ema) => signal_ema.next(&ppo),
+            None => match EMA::new(self.cfg.signal, &ppo) {
+                Ok(signal_ema) => self.signal_ema.insert(signal_ema).next(&ppo),
+                Err(_) => return IndicatorResult::new(&[], &[Action::None]),
+            },
+        };
+        let histogram = ppo - signal;
+
+        let action = match self.prev_histogram {
+            Some(prev) if prev <= 0. && histogram > 0. => Action::Buy(1),
+            Some(prev) if prev >= 0. && histogram < 0. => Action::Sell(1),
+            _ => Action::None,
+        };
+        self.prev_histogram = Some(histogram);
+
+        IndicatorResult::new(&[ppo, signal, histogram], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for PPOInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.slow as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use std::f64::consts::PI;
+
+    // Plain MACD over the same EMAs, returning crossover directions by candle index
+    fn macd_crossovers(closes: &[f64]) -> Result<Vec<(usize, Action)>> {
+        let mut fast_ema = EMA::new(12, &closes[0])?;
+        let mut slow_ema = EMA::new(26, &closes[0])?;
+        let mut signal_ema: Option<EMA> = None;
+        let mut prev_histogram: Option<f64> = None;
+        let mut crossovers = Vec::new();
+        for (i, close) in closes.iter().enumerate() {
+            let macd = fast_ema.next(close) - slow_ema.next(close);
+            if i + 1 < 26 {
+                continue;
+            }
+            let signal = match signal_ema.as_mut() {
+                Some(signal_ema) => signal_ema.next(&macd),
+                None => signal_ema.insert(EMA::new(9, &macd)?).next(&macd),
+            };
+            let histogram = macd - signal;
+            match prev_histogram {
+                Some(prev) if prev <= 0. && histogram > 0. => crossovers.push((i, Action::Buy(1))),
+                Some(prev) if prev >= 0. && histogram < 0. => crossovers.push((i, Action::Sell(1))),
+                _ => {}
+            }
+            prev_histogram = Some(histogram);
+        }
+        Ok(crossovers)
+    }
+
+    #[test]
+    fn test_ppo_crossovers_match_macd() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (0..300).map(|i| 100.0 + 20.0 * (2.0 * PI * i as f64 / 60.0).sin() + 0.05 * i as f64).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut ppo = PpoConfig::default().init(first_kline)?;
+        let mut ppo_crossovers = Vec::new();
+        for (i, kline) in klines.iter().enumerate() {
+            let result = ppo.next_binance_kline(kline);
+            if let Some(value) = result.values().first() {
+                assert!(value.abs() < 100.);
+            }
+            if result.signals()[0] != Action::None {
+                ppo_crossovers.push((i, result.signals()[0]));
+            }
+        }
+
+        let macd_crossovers = macd_crossovers(&closes)?;
+        assert!(ppo_crossovers.len() >= 8);
+        assert_eq!(ppo_crossovers.len(), macd_crossovers.len());
+        for ((ppo_index, ppo_action), (macd_index, macd_action)) in ppo_crossovers.iter().zip(&macd_crossovers) {
+            assert_eq!(ppo_action, macd_action);
+            assert!(ppo_index.abs_diff(*macd_index) <= 1);
+        }
+
+        Ok(())
+    }
+}
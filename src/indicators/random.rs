@@ -0,0 +1,66 @@
+use crate::data::BinanceKline;
+use crate::indicators::BinanceIndicatorInstance;
+use crate::rng::SplitMix64;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+/// Coin flip entries and exits, a null hypothesis baseline for a real strategy.
+/// Set `entry_prob` to the strategy's trades per candle to match its trade frequency.
+#[derive(Debug, Clone, Copy)]
+pub struct RandomConfig {
+    pub entry_prob: f64,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RandomInstance {
+    cfg: RandomConfig,
+    rng: SplitMix64,
+    long: bool,
+}
+
+impl IndicatorConfig for RandomConfig {
+    type Instance = RandomInstance;
+
+    const NAME: &'static str = "Random";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Random entry probability must be between 0 and 1")));
+        }
+        Ok(Self::Instance { rng: SplitMix64::new(self.seed), long: false, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        (0.0..=1.0).contains(&self.entry_prob)
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (0, 1)
+    }
+}
+
+impl IndicatorInstance for RandomInstance {
+    type Config = RandomConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    // Flat it flips to enter, long it flips to exit, so signals alternate like a real strategy's
+    fn next<T: OHLCV>(&mut self, _candle: &T) -> IndicatorResult {
+        if self.rng.next_f64() >= self.cfg.entry_prob {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+        self.long = !self.long;
+        let action = if self.long { Action::Buy(1) } else { Action::Sell(1) };
+        IndicatorResult::new(&[], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for RandomInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
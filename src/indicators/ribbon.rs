@@ -0,0 +1,101 @@
+use crate::data::BinanceKline;
+use crate::indicators::BinanceIndicatorInstance;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::methods::EMA;
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct RibbonConfig {
+    /// EMA periods from the shortest to the longest, at most 255 each
+    pub periods: Vec<usize>,
+}
+
+impl Default for RibbonConfig {
+    fn default() -> Self {
+        Self { periods: vec![10, 17, 24, 31, 38, 45, 52, 60] }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stack {
+    Bullish,
+    Bearish,
+}
+
+#[derive(Debug, Clone)]
+pub struct RibbonInstance {
+    cfg: RibbonConfig,
+    emas: Vec<EMA>,
+    candles_seen: usize,
+    last_stack: Option<Stack>,
+}
+
+impl IndicatorConfig for RibbonConfig {
+    type Instance = RibbonInstance;
+
+    const NAME: &'static str = "Ribbon";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Ribbon needs at least two increasing EMA periods between 1 and 255")));
+        }
+        let emas = self.periods.iter().map(|period| EMA::new(*period as u8, &candle.close())).collect::<Result<Vec<EMA>, Error>>()?;
+        Ok(Self::Instance { emas, candles_seen: 0, last_stack: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.periods.len() >= 2
+            && self.periods.first().is_some_and(|period| *period > 0)
+            && self.periods.last().is_some_and(|period| *period <= u8::MAX as usize)
+            && self.periods.windows(2).all(|pair| pair[0] < pair[1])
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (0, 1)
+    }
+}
+
+impl IndicatorInstance for RibbonInstance {
+    type Config = RibbonConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    // Signals only when the ribbon newly stacks in one direction, a tangled ribbon keeps the last stack
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let values: Vec<f64> = self.emas.iter_mut().map(|ema| ema.next(&candle.close())).collect();
+        self.candles_seen += 1;
+        if self.candles_seen < self.warm_up_period() {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let stack = if values.windows(2).all(|pair| pair[0] > pair[1]) {
+            Some(Stack::Bullish)
+        } else if values.windows(2).all(|pair| pair[0] < pair[1]) {
+            Some(Stack::Bearish)
+        } else {
+            None
+        };
+        let action = match stack {
+            Some(stack) if self.last_stack == Some(stack) => Action::None,
+            Some(Stack::Bullish) => Action::Buy(1),
+            Some(Stack::Bearish) => Action::Sell(1),
+            None => Action::None,
+        };
+        self.last_stack = stack.or(self.last_stack);
+
+        IndicatorResult::new(&[], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for RibbonInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.periods.last().copied().unwrap_or_default()
+    }
+}
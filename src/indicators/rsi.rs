@@ -0,0 +1,149 @@
+use crate::data::BinanceKline;
+use crate::indicators::BinanceIndicatorInstance;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct RsiConfig {
+    pub period: usize,
+    pub overbought: f64,
+    pub oversold: f64,
+}
+
+impl Default for RsiConfig {
+    fn default() -> Self {
+        Self { period: 14, overbought: 70., oversold: 30. }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RSIInstance {
+    cfg: RsiConfig,
+    prev_close: f64,
+    changes_seen: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+    prev_rsi: Option<f64>,
+}
+
+impl RSIInstance {
+    fn rsi(&self) -> f64 {
+        if self.avg_loss > 0. {
+            100. - 100. / (1. + self.avg_gain / self.avg_loss)
+        } else if self.avg_gain > 0. {
+            100.
+        } else {
+            50.
+        }
+    }
+}
+
+impl IndicatorConfig for RsiConfig {
+    type Instance = RSIInstance;
+
+    const NAME: &'static str = "RSI";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("RSI period must be positive and oversold below overbought within 0 to 100")));
+        }
+        Ok(Self::Instance { prev_close: candle.close(), changes_seen: 0, avg_gain: 0., avg_loss: 0., prev_rsi: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0 && 0. < self.oversold && self.oversold < self.overbought && self.overbought < 100.
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (1, 1)
+    }
+}
+
+impl IndicatorInstance for RSIInstance {
+    type Config = RsiConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let change = candle.close() - self.prev_close;
+        self.prev_close = candle.close();
+        let gain = change.max(0.);
+        let loss = (-change).max(0.);
+
+        // Simple average over the first period, Wilder smoothing afterwards
+        let period = self.cfg.period as f64;
+        self.changes_seen += 1;
+        if self.changes_seen <= self.cfg.period {
+            self.avg_gain += gain / period;
+            self.avg_loss += loss / period;
+            if self.changes_seen < self.cfg.period {
+                return IndicatorResult::new(&[], &[Action::None]);
+            }
+        } else {
+            self.avg_gain = (self.avg_gain * (period - 1.) + gain) / period;
+            self.avg_loss = (self.avg_loss * (period - 1.) + loss) / period;
+        }
+
+        let rsi = self.rsi();
+        let action = match self.prev_rsi {
+            Some(prev) if prev < self.cfg.oversold && rsi >= self.cfg.oversold => Action::Buy(1),
+            Some(prev) if prev > self.cfg.overbought && rsi <= self.cfg.overbought => Action::Sell(1),
+            _ => Action::None,
+        };
+        self.prev_rsi = Some(rsi);
+
+        IndicatorResult::new(&[rsi], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for RSIInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.period
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    fn run_rsi(config: RsiConfig, closes: &[f64]) -> Result<Vec<IndicatorResult>> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(closes, start_time, Duration::hours(1));
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut rsi = config.init(first_kline)?;
+        Ok(klines.iter().skip(1).map(|kline| rsi.next_binance_kline(kline)).collect())
+    }
+
+    #[test]
+    fn test_rsi_value_with_wilder_smoothing() -> Result<()> {
+        let results = run_rsi(RsiConfig { period: 2, ..RsiConfig::default() }, &[10.0, 12.0, 11.0, 13.0])?;
+
+        assert!(results[0].values().is_empty());
+        // Seeded with gain 1 and loss 0.5, then smoothed with a gain of 2
+        let last_result = results.last().ok_or(anyhow!("No results"))?;
+        assert!((last_result.values()[0] - 100.0 * 1.5 / 1.75).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rsi_signals_when_leaving_extremes() -> Result<()> {
+        let closes = [100.0, 90.0, 80.0, 70.0, 60.0, 80.0, 100.0, 120.0, 140.0, 120.0, 100.0];
+        let results = run_rsi(RsiConfig { period: 3, ..RsiConfig::default() }, &closes)?;
+
+        let signals: Vec<Action> = results.iter().map(|result| result.signals()[0]).collect();
+        assert_eq!(signals, vec![Action::None, Action::None, Action::None, Action::None, Action::Buy(1), Action::None, Action::None, Action::None, Action::Sell(1), Action::None]);
+
+        Ok(())
+    }
+}
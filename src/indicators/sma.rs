@@ -1,5 +1,5 @@
-use crate::data::BinanceKline;
-use crate::indicators::BinanceIndicatorInstance;
+use crate::data::{BinanceKline, TimestampConvention};
+use crate::indicators::{crossover_action, BinanceIndicatorInstance, CrossoverState, SignalMode, TieBreak};
 use anyhow::Result;
 use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use yata::core::{Action, Error, IndicatorResult, OHLCV};
@@ -10,11 +10,30 @@ use yata::prelude::*;
 pub struct SmaPair {
     short_window: u8,
     long_window: u8,
+    tie_break: TieBreak,
+    signal_mode: SignalMode,
+    history: Option<Vec<f64>>,
 }
 
 impl SmaPair {
     pub fn new(short_window: u8, long_window: u8) -> Self {
-        Self { short_window, long_window }
+        Self { short_window, long_window, tie_break: TieBreak::default(), signal_mode: SignalMode::default(), history: None }
+    }
+
+    /// Closes preceding the first traded candle, used to warm both averages instead of repeating the first close
+    pub fn with_history(mut self, history: &[f64]) -> Self {
+        self.history = Some(history.to_vec());
+        self
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn with_signal_mode(mut self, signal_mode: SignalMode) -> Self {
+        self.signal_mode = signal_mode;
+        self
     }
 }
 
@@ -24,18 +43,33 @@ pub struct SMAInstance {
     sma1: SMA,
     sma2: SMA,
     last_timestamp: NaiveDateTime,
+    timestamp_convention: TimestampConvention,
+    history_len: usize,
+    crossover: CrossoverState,
+    last_signal: Action,
 }
 
 impl SMAInstance {
-    fn new(cfg: SmaPair, first_value: f64) -> Result<Self, Error> {
+    fn new(cfg: SmaPair, first_value: f64, history: Option<&[f64]>) -> Result<Self, Error> {
         let short_window = cfg.short_window;
         let long_window = cfg.long_window;
+        let (seed, rest) = history.and_then(|history| history.split_first()).unwrap_or((&first_value, &[][..]));
+        let mut sma1 = SMA::new(short_window, seed)?;
+        let mut sma2 = SMA::new(long_window, seed)?;
+        for value in rest {
+            sma1.next(value);
+            sma2.next(value);
+        }
 
         Ok(Self {
             last_timestamp: NaiveDate::from_ymd_opt(2000, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(Error::Other(String::from("Could not create last_timestamp")))?, // FIXME: a magic date before crypto happens
+            timestamp_convention: TimestampConvention::default(),
+            history_len: history.map_or(0, <[f64]>::len),
+            crossover: CrossoverState::default(),
             cfg,
-            sma1: SMA::new(short_window, &first_value)?,
-            sma2: SMA::new(long_window, &first_value)?,
+            last_signal: Action::None,
+            sma1,
+            sma2,
         })
     }
 }
@@ -45,8 +79,9 @@ impl IndicatorConfig for SmaPair {
 
     const NAME: &'static str = "SMA";
 
-    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
-        Self::Instance::new(self, candle.close())
+    fn init<T: OHLCV>(mut self, candle: &T) -> Result<Self::Instance, Error> {
+        let history = self.history.take();
+        Self::Instance::new(self, candle.close(), history.as_deref())
     }
     fn validate(&self) -> bool {
         true
@@ -73,7 +108,7 @@ impl IndicatorInstance for SMAInstance {
 
 impl BinanceIndicatorInstance for SMAInstance {
     fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
-        let current_time = candle.start_time;
+        let current_time = candle.timestamp(self.timestamp_convention);
         let current_month = current_time.month();
         let last_month = self.last_timestamp.month();
         self.last_timestamp = current_time;
@@ -81,16 +116,71 @@ impl BinanceIndicatorInstance for SMAInstance {
         let short_ma = self.sma1.next(&candle.close);
         let long_ma = self.sma2.next(&candle.close);
 
-        let action = if current_month == last_month {
-            Action::None
-        } else if short_ma > long_ma {
-            Action::Buy(1)
-        } else if short_ma < long_ma {
-            Action::Sell(1)
-        } else {
-            Action::None
+        let side = crossover_action(short_ma, long_ma, self.cfg.tie_break, self.last_signal);
+        let action = match self.cfg.signal_mode {
+            SignalMode::Crossover => self.crossover.flip(side),
+            SignalMode::Monthly if current_month != last_month => side,
+            SignalMode::Monthly => Action::None,
         };
+        if action != Action::None {
+            self.last_signal = action;
+        }
 
         IndicatorResult::new(&[], &[action])
     }
+
+    fn warm_up_period(&self) -> usize {
+        (self.cfg.long_window as usize).saturating_sub(self.history_len)
+    }
+
+    fn set_timestamp_convention(&mut self, timestamp_convention: TimestampConvention) {
+        self.timestamp_convention = timestamp_convention;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::Duration;
+
+    #[test]
+    fn test_history_warm_starts_averages() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (1..=30).map(f64::from).collect();
+        let klines = klines_from_closes(&closes[20..], start_time, Duration::hours(1));
+
+        let mut cold = SmaPair::new(3, 10).init(&klines[0])?;
+        let mut warm = SmaPair::new(3, 10).with_history(&closes[..20]).init(&klines[0])?;
+        assert_eq!(cold.warm_up_period(), 10);
+        assert_eq!(warm.warm_up_period(), 0);
+
+        // The steady-state averages of the closes ending at 21, a cold start is still anchored to the first close
+        assert!((warm.sma2.next(&closes[20]) - 16.5).abs() < 1e-9);
+        assert!((warm.sma1.next(&closes[20]) - 20.0).abs() < 1e-9);
+        assert!((cold.sma2.next(&closes[20]) - 21.0).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_crossover_mode_fires_mid_month() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (0..14).map(|day| 200.0 - f64::from(day)).chain((1..=7).map(|day| 187.0 + 5.0 * f64::from(day))).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::days(1));
+
+        let signals = |signal_mode: SignalMode| -> Result<Vec<Action>> {
+            let mut sma = SmaPair::new(2, 5).with_signal_mode(signal_mode).init(&klines[0])?;
+            Ok(klines.iter().map(|kline| sma.next_binance_kline(kline).signals()[0]).collect())
+        };
+
+        // The averages cross on the 15th, the monthly mode waits for February
+        let crossover = signals(SignalMode::Crossover)?;
+        assert_eq!(crossover[14], Action::Buy(1));
+        assert_eq!(crossover.iter().filter(|action| **action != Action::None).count(), 1);
+        assert!(signals(SignalMode::Monthly)?.iter().all(|action| *action == Action::None));
+
+        Ok(())
+    }
 }
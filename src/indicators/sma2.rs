@@ -1,9 +1,11 @@
-use crate::data::BinanceKline;
-use crate::indicators::BinanceIndicatorInstance;
+use crate::data::{BinanceKline, TimestampConvention};
+use crate::indicators::{crossover_action, BinanceIndicatorInstance, CrossoverState, FeatureCache, SignalMode, TieBreak};
 use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use yata::core::{Action, Error, IndicatorResult, OHLCV};
 use yata::prelude::*;
 
+use std::sync::Arc;
+
 #[derive(Debug, Clone, Default)]
 pub struct Sma2 {
     window_size: usize,
@@ -11,11 +13,12 @@ pub struct Sma2 {
 }
 
 impl Sma2 {
-    pub fn new(window_size: usize, value: f64) -> Self {
-        Self { window_size, prices: vec![value] }
+    pub fn new(window_size: usize, value: f64, history: Option<&[f64]>) -> Self {
+        let prices = history.filter(|history| !history.is_empty()).map_or_else(|| vec![value], <[f64]>::to_vec);
+        Self { window_size, prices }
     }
 
-    fn update_price(&mut self, price: f64) -> Option<f64> {
+    pub(crate) fn update_price(&mut self, price: f64) -> Option<f64> {
         self.prices.push(price);
         self.moving_average()
     }
@@ -33,11 +36,37 @@ impl Sma2 {
 pub struct Sma2Pair {
     short_window: usize,
     long_window: usize,
+    tie_break: TieBreak,
+    signal_mode: SignalMode,
+    history: Option<Vec<f64>>,
+    cache: Option<Arc<FeatureCache>>,
 }
 
 impl Sma2Pair {
     pub fn new(short_window: usize, long_window: usize) -> Self {
-        Self { short_window, long_window }
+        Self { short_window, long_window, tie_break: TieBreak::default(), signal_mode: SignalMode::default(), history: None, cache: None }
+    }
+
+    /// Read both averages from a shared cache built over the same klines instead of computing them, cannot be combined with `with_history`
+    pub fn with_cache(mut self, cache: Arc<FeatureCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Closes preceding the first traded candle, used to fill both windows up front
+    pub fn with_history(mut self, history: &[f64]) -> Self {
+        self.history = Some(history.to_vec());
+        self
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn with_signal_mode(mut self, signal_mode: SignalMode) -> Self {
+        self.signal_mode = signal_mode;
+        self
     }
 }
 
@@ -47,18 +76,28 @@ pub struct SMA2Instance {
     sma1: Sma2,
     sma2: Sma2,
     last_timestamp: NaiveDateTime,
+    timestamp_convention: TimestampConvention,
+    history_len: usize,
+    crossover: CrossoverState,
+    cursor: usize,
+    last_signal: Action,
 }
 
 impl SMA2Instance {
-    fn new(cfg: Sma2Pair, first_value: f64) -> Result<Self, Error> {
+    fn new(cfg: Sma2Pair, first_value: f64, history: Option<&[f64]>) -> Result<Self, Error> {
         let short_window = cfg.short_window;
         let long_window = cfg.long_window;
 
         Ok(Self {
             last_timestamp: NaiveDate::from_ymd_opt(2000, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(Error::Other(String::from("Could not create last_timestamp")))?, // FIXME: a magic date before crypto happens
+            timestamp_convention: TimestampConvention::default(),
+            history_len: history.map_or(0, <[f64]>::len),
+            crossover: CrossoverState::default(),
+            cursor: 0,
             cfg,
-            sma1: Sma2::new(short_window, first_value),
-            sma2: Sma2::new(long_window, first_value),
+            last_signal: Action::None,
+            sma1: Sma2::new(short_window, first_value, history),
+            sma2: Sma2::new(long_window, first_value, history),
         })
     }
 }
@@ -68,8 +107,18 @@ impl IndicatorConfig for Sma2Pair {
 
     const NAME: &'static str = "SMA2";
 
-    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
-        Self::Instance::new(self, candle.close())
+    fn init<T: OHLCV>(mut self, candle: &T) -> Result<Self::Instance, Error> {
+        if let Some(cache) = &self.cache {
+            // The cache is seeded from the first kline, so warming up from history would silently change nothing
+            if self.history.is_some() {
+                return Err(Error::Other(String::from("Feature cache cannot be combined with warm-up history")));
+            }
+            if !cache.has_sma(self.short_window) || !cache.has_sma(self.long_window) {
+                return Err(Error::Other(format!("Feature cache has no SMA for windows {} and {}", self.short_window, self.long_window)));
+            }
+        }
+        let history = self.history.take();
+        Self::Instance::new(self, candle.close(), history.as_deref())
     }
     fn validate(&self) -> bool {
         true
@@ -96,24 +145,105 @@ impl IndicatorInstance for SMA2Instance {
 
 impl BinanceIndicatorInstance for SMA2Instance {
     fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
-        let current_time = candle.start_time;
+        let current_time = candle.timestamp(self.timestamp_convention);
         let current_month = current_time.month();
         let last_month = self.last_timestamp.month();
         self.last_timestamp = current_time;
 
-        let short_ma = self.sma1.update_price(candle.close);
-        let long_ma = self.sma2.update_price(candle.close);
-
-        let action = if current_month == last_month {
-            Action::None
-        } else if short_ma > long_ma {
-            Action::Buy(1)
-        } else if short_ma < long_ma {
-            Action::Sell(1)
-        } else {
-            Action::None
+        let (short_ma, long_ma) = match &self.cfg.cache {
+            Some(cache) => (cache.sma(self.cfg.short_window, self.cursor), cache.sma(self.cfg.long_window, self.cursor)),
+            None => (self.sma1.update_price(candle.close), self.sma2.update_price(candle.close)),
+        };
+        self.cursor += 1;
+
+        let side = match (short_ma, long_ma) {
+            (Some(short_ma), Some(long_ma)) => crossover_action(short_ma, long_ma, self.cfg.tie_break, self.last_signal),
+            _ => Action::None,
+        };
+        let action = match self.cfg.signal_mode {
+            SignalMode::Crossover => self.crossover.flip(side),
+            SignalMode::Monthly if current_month != last_month => side,
+            SignalMode::Monthly => Action::None,
         };
+        if action != Action::None {
+            self.last_signal = action;
+        }
 
         IndicatorResult::new(&[], &[action])
     }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.long_window.saturating_sub(self.history_len)
+    }
+
+    fn set_timestamp_convention(&mut self, timestamp_convention: TimestampConvention) {
+        self.timestamp_convention = timestamp_convention;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::Duration;
+
+    fn create_kline(year: i32, month: u32, close: f64) -> Result<BinanceKline> {
+        let timestamp = NaiveDate::from_ymd_opt(year, month, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        Ok(BinanceKline { start_time: timestamp, open: close, close, high: close, low: close, volume: 1.0, end_time: timestamp })
+    }
+
+    fn action_on_equal_averages(tie_break: TieBreak) -> Result<Action> {
+        let klines = [create_kline(2024, 2, 90.0)?, create_kline(2024, 3, 90.0)?];
+        let mut sma = Sma2Pair::new(1, 2).with_tie_break(tie_break).with_signal_mode(SignalMode::Monthly).init(&create_kline(2024, 1, 100.0)?)?;
+
+        let result = sma.next_binance_kline(&klines[0]);
+        assert_eq!(result.signals()[0], Action::Sell(1));
+
+        let result = sma.next_binance_kline(&klines[1]);
+        Ok(result.signals()[0])
+    }
+
+    #[test]
+    fn test_tie_break_modes() -> Result<()> {
+        assert_eq!(action_on_equal_averages(TieBreak::None)?, Action::None);
+        assert_eq!(action_on_equal_averages(TieBreak::HoldPrevious)?, Action::Sell(1));
+        assert_eq!(action_on_equal_averages(TieBreak::TreatAsBull)?, Action::Buy(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_signal_while_long_window_warms_up() -> Result<()> {
+        let mut sma = Sma2Pair::new(1, 4).with_signal_mode(SignalMode::Monthly).init(&create_kline(2024, 1, 100.0)?)?;
+
+        for (month, close) in [(2, 110.0), (3, 120.0)] {
+            let result = sma.next_binance_kline(&create_kline(2024, month, close)?);
+            assert_eq!(result.signals()[0], Action::None);
+        }
+
+        let result = sma.next_binance_kline(&create_kline(2024, 4, 130.0)?);
+        assert_eq!(result.signals()[0], Action::Buy(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_crossover_mode_fires_mid_month() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (0..14).map(|day| 200.0 - f64::from(day)).chain((1..=7).map(|day| 187.0 + 5.0 * f64::from(day))).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::days(1));
+
+        let signals = |signal_mode: SignalMode| -> Result<Vec<Action>> {
+            let mut sma = Sma2Pair::new(2, 5).with_signal_mode(signal_mode).init(&klines[0])?;
+            Ok(klines.iter().map(|kline| sma.next_binance_kline(kline).signals()[0]).collect())
+        };
+
+        let crossover = signals(SignalMode::Crossover)?;
+        assert_eq!(crossover[14], Action::Buy(1));
+        assert_eq!(crossover.iter().filter(|action| **action != Action::None).count(), 1);
+        assert!(signals(SignalMode::Monthly)?.iter().all(|action| *action == Action::None));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,140 @@
+use crate::data::BinanceKline;
+use crate::indicators::bollinger::BollingerInstance;
+use crate::indicators::keltner::KeltnerInstance;
+use crate::indicators::{BinanceIndicatorInstance, BollingerConfig, KeltnerConfig};
+use std::collections::VecDeque;
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct SqueezeConfig {
+    pub bb: BollingerConfig,
+    pub keltner: KeltnerConfig,
+    pub momentum_period: usize,
+}
+
+impl Default for SqueezeConfig {
+    fn default() -> Self {
+        Self { bb: BollingerConfig::default(), keltner: KeltnerConfig::default(), momentum_period: 12 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SqueezeInstance {
+    cfg: SqueezeConfig,
+    bb: BollingerInstance,
+    keltner: KeltnerInstance,
+    closes: VecDeque<f64>,
+    squeeze_on: Option<bool>,
+}
+
+impl IndicatorConfig for SqueezeConfig {
+    type Instance = SqueezeInstance;
+
+    const NAME: &'static str = "Squeeze";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Squeeze momentum period must be positive")));
+        }
+        Ok(Self::Instance { bb: self.bb.clone().init(candle)?, keltner: self.keltner.clone().init(candle)?, closes: VecDeque::with_capacity(self.momentum_period + 1), squeeze_on: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.momentum_period > 0 && self.bb.validate() && self.keltner.validate()
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 1)
+    }
+}
+
+impl IndicatorInstance for SqueezeInstance {
+    type Config = SqueezeConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.bb.next(candle);
+        self.keltner.next(candle);
+        self.closes.push_back(candle.close());
+        if self.closes.len() > self.cfg.momentum_period + 1 {
+            self.closes.pop_front();
+        }
+
+        let (Some((bb_lower, _, bb_upper)), Some((kc_lower, _, kc_upper))) = (self.bb.bands(), self.keltner.bands()) else {
+            return IndicatorResult::new(&[], &[Action::None]);
+        };
+        let squeeze_on = bb_lower > kc_lower && bb_upper < kc_upper;
+        let momentum = match self.closes.front() {
+            Some(past) if self.closes.len() > self.cfg.momentum_period => candle.close() - past,
+            _ => 0.,
+        };
+
+        // Fire in the direction of momentum when the squeeze releases
+        let action = match self.squeeze_on {
+            Some(true) if !squeeze_on && momentum > 0. => Action::Buy(1),
+            Some(true) if !squeeze_on && momentum < 0. => Action::Sell(1),
+            _ => Action::None,
+        };
+        self.squeeze_on = Some(squeeze_on);
+
+        IndicatorResult::new(&[if squeeze_on { 1. } else { 0. }, momentum], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for SqueezeInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.bb.period.max(self.cfg.keltner.period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_squeeze_fires_on_release() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let quiet = (0..40).map(|i| 100.0 + 0.2 * (i % 2) as f64);
+        let breakout = (1..15).map(|i| 100.2 + 3.0 * i as f64);
+        let closes: Vec<f64> = quiet.chain(breakout).collect();
+        let mut klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+        for kline in klines.iter_mut() {
+            kline.high = kline.close + 1.0;
+            kline.low = kline.close - 1.0;
+        }
+
+        let config = SqueezeConfig::default();
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut squeeze = config.init(first_kline)?;
+
+        let mut states = Vec::new();
+        for (i, kline) in klines.iter().enumerate() {
+            let result = squeeze.next_binance_kline(kline);
+            if let Some(state) = result.values().first() {
+                states.push((i, *state == 1., result.signals()[0]));
+            }
+        }
+
+        let release = states.iter().position(|(_, _, action)| *action != Action::None).ok_or(anyhow!("Squeeze never released"))?;
+        assert_eq!(states[0].0, 19);
+        assert!(states[..release].iter().all(|(_, squeeze_on, _)| *squeeze_on));
+        assert!(states[release].0 >= 40);
+        assert_eq!(states[release].2, Action::Buy(1));
+        assert!(states[release..].iter().all(|(_, squeeze_on, _)| !*squeeze_on));
+        assert_eq!(states.iter().filter(|(_, _, action)| *action != Action::None).count(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+const OVERSOLD: f64 = 30.;
+const OVERBOUGHT: f64 = 70.;
+
+#[derive(Debug, Clone)]
+pub struct UltimateConfig {
+    pub short: usize,
+    pub mid: usize,
+    pub long: usize,
+}
+
+impl Default for UltimateConfig {
+    fn default() -> Self {
+        Self { short: 7, mid: 14, long: 28 }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PressureWindow {
+    buying_pressure: RollingWindow,
+    true_ranges: RollingWindow,
+}
+
+impl PressureWindow {
+    fn new(period: usize) -> Self {
+        Self { buying_pressure: RollingWindow::new(period), true_ranges: RollingWindow::new(period) }
+    }
+
+    fn push(&mut self, buying_pressure: f64, true_range: f64) {
+        self.buying_pressure.push(buying_pressure);
+        self.true_ranges.push(true_range);
+    }
+
+    fn average(&self) -> f64 {
+        let true_range = self.true_ranges.sum();
+        if true_range > 0. {
+            self.buying_pressure.sum() / true_range
+        } else {
+            0.5
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UltimateInstance {
+    cfg: UltimateConfig,
+    prev_close: f64,
+    short: PressureWindow,
+    mid: PressureWindow,
+    long: PressureWindow,
+    last_oversold: Option<(f64, f64)>,
+    last_overbought: Option<(f64, f64)>,
+}
+
+impl IndicatorConfig for UltimateConfig {
+    type Instance = UltimateInstance;
+
+    const NAME: &'static str = "Ultimate";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Ultimate periods must be positive and ordered short < mid < long")));
+        }
+        Ok(Self::Instance {
+            prev_close: candle.close(),
+            short: PressureWindow::new(self.short),
+            mid: PressureWindow::new(self.mid),
+            long: PressureWindow::new(self.long),
+            last_oversold: None,
+            last_overbought: None,
+            cfg: self,
+        })
+    }
+    fn validate(&self) -> bool {
+        self.short > 0 && self.short < self.mid && self.mid < self.long
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (1, 1)
+    }
+}
+
+impl IndicatorInstance for UltimateInstance {
+    type Config = UltimateConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let true_low = candle.low().min(self.prev_close);
+        let true_high = candle.high().max(self.prev_close);
+        let buying_pressure = candle.close() - true_low;
+        let true_range = true_high - true_low;
+        self.prev_close = candle.close();
+
+        self.short.push(buying_pressure, true_range);
+        self.mid.push(buying_pressure, true_range);
+        self.long.push(buying_pressure, true_range);
+        if !self.long.true_ranges.is_full() {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let value = 100. * (4. * self.short.average() + 2. * self.mid.average() + self.long.average()) / 7.;
+        let close = candle.close();
+
+        // Only act on an extreme reading when price and oscillator diverge from the previous extreme reading
+        let mut action = Action::None;
+        if value < OVERSOLD {
+            if let Some((last_close, last_value)) = self.last_oversold {
+                if close < last_close && value > last_value {
+                    action = Action::Buy(1);
+                }
+            }
+            self.last_oversold = Some((close, value));
+        } else if value > OVERBOUGHT {
+            if let Some((last_close, last_value)) = self.last_overbought {
+                if close > last_close && value < last_value {
+                    action = Action::Sell(1);
+                }
+            }
+            self.last_overbought = Some((close, value));
+        }
+
+        IndicatorResult::new(&[value], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for UltimateInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))
+    }
+
+    fn create_kline(open: f64, high: f64, low: f64, close: f64) -> Result<BinanceKline> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        Ok(BinanceKline { start_time: timestamp, open, close, high, low, volume: 1.0, end_time: timestamp })
+    }
+
+    #[test]
+    fn test_ultimate_oscillator_value() -> Result<()> {
+        let klines = [
+            create_kline(10.0, 12.0, 9.0, 11.0)?,
+            create_kline(11.0, 13.0, 10.0, 12.0)?,
+            create_kline(12.0, 12.0, 10.0, 10.0)?,
+            create_kline(10.0, 11.0, 8.0, 9.0)?,
+            create_kline(9.0, 12.0, 9.0, 12.0)?,
+        ];
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut ultimate = UltimateConfig { short: 2, mid: 3, long: 4 }.init(first_kline)?;
+
+        let results: Vec<IndicatorResult> = klines.iter().map(|kline| ultimate.next_binance_kline(kline)).collect();
+
+        let expected = 100.0 * (4.0 * (4.0 / 6.0) + 2.0 * (4.0 / 8.0) + 6.0 / 11.0) / 7.0;
+        let last_result = results.last().ok_or(anyhow!("No results"))?;
+        assert!((last_result.values()[0] - expected).abs() < 1e-9);
+        assert_eq!(last_result.signals()[0], Action::None);
+
+        Ok(())
+    }
+}
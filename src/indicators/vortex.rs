@@ -0,0 +1,133 @@
+use crate::data::BinanceKline;
+use crate::indicators::{true_range, BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct VortexConfig {
+    pub period: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct VortexInstance {
+    cfg: VortexConfig,
+    prev_high: f64,
+    prev_low: f64,
+    prev_close: f64,
+    plus_movements: RollingWindow,
+    minus_movements: RollingWindow,
+    true_ranges: RollingWindow,
+    prev_spread: Option<f64>,
+}
+
+impl IndicatorConfig for VortexConfig {
+    type Instance = VortexInstance;
+
+    const NAME: &'static str = "Vortex";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("Vortex period must be positive")));
+        }
+        Ok(Self::Instance {
+            prev_high: candle.high(),
+            prev_low: candle.low(),
+            prev_close: candle.close(),
+            plus_movements: RollingWindow::new(self.period),
+            minus_movements: RollingWindow::new(self.period),
+            true_ranges: RollingWindow::new(self.period),
+            prev_spread: None,
+            cfg: self,
+        })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 1)
+    }
+}
+
+impl IndicatorInstance for VortexInstance {
+    type Config = VortexConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        self.plus_movements.push((candle.high() - self.prev_low).abs());
+        self.minus_movements.push((candle.low() - self.prev_high).abs());
+        self.true_ranges.push(true_range(candle, self.prev_close));
+        self.prev_high = candle.high();
+        self.prev_low = candle.low();
+        self.prev_close = candle.close();
+
+        let range_sum = self.true_ranges.sum();
+        if !self.true_ranges.is_full() || range_sum <= 0. {
+            return IndicatorResult::new(&[], &[Action::None]);
+        }
+
+        let vi_plus = self.plus_movements.sum() / range_sum;
+        let vi_minus = self.minus_movements.sum() / range_sum;
+        let spread = vi_plus - vi_minus;
+
+        let action = match self.prev_spread {
+            Some(prev_spread) if prev_spread <= 0. && spread > 0. => Action::Buy(1),
+            Some(prev_spread) if prev_spread >= 0. && spread < 0. => Action::Sell(1),
+            _ => Action::None,
+        };
+        self.prev_spread = Some(spread);
+
+        IndicatorResult::new(&[vi_plus, vi_minus], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for VortexInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))
+    }
+
+    fn create_kline(low: f64, high: f64) -> Result<BinanceKline> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        Ok(BinanceKline { start_time: timestamp, open: low, close: high, high, low, volume: 1.0, end_time: timestamp })
+    }
+
+    #[test]
+    fn test_vortex_buys_when_trend_turns_up() -> Result<()> {
+        let mut klines = Vec::new();
+        for i in 0..6 {
+            klines.push(create_kline(100.0 - 2.0 * i as f64, 103.0 - 2.0 * i as f64)?);
+        }
+        for i in 0..6 {
+            klines.push(create_kline(92.0 + 2.0 * i as f64, 95.0 + 2.0 * i as f64)?);
+        }
+
+        let first_kline = klines.first().ok_or(anyhow!("No klines"))?;
+        let mut vortex = VortexConfig { period: 3 }.init(first_kline)?;
+        let results: Vec<IndicatorResult> = klines.iter().map(|kline| vortex.next_binance_kline(kline)).collect();
+        let actions: Vec<Action> = results.iter().map(|result| result.signals()[0]).collect();
+
+        assert_eq!(actions.iter().filter(|action| **action == Action::Buy(1)).count(), 1);
+        assert!(!actions.contains(&Action::Sell(1)));
+
+        let last_values = results.last().ok_or(anyhow!("No results"))?.values();
+        assert!(last_values[0] > last_values[1]);
+
+        Ok(())
+    }
+}
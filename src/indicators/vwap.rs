@@ -0,0 +1,116 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+fn typical_price<T: OHLCV>(candle: &T) -> f64 {
+    (candle.high() + candle.low() + candle.close()) / 3.
+}
+
+// Zero-volume policy: candles with zero (or negative) volume add nothing to the window, and when the whole
+// window carries no volume the previous VWAP is carried forward instead of dividing by zero
+#[derive(Debug, Clone)]
+pub struct VwapConfig {
+    pub period: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct VWAPInstance {
+    cfg: VwapConfig,
+    weighted_prices: RollingWindow,
+    volumes: RollingWindow,
+    vwap: f64,
+    prev_close: f64,
+}
+
+impl IndicatorConfig for VwapConfig {
+    type Instance = VWAPInstance;
+
+    const NAME: &'static str = "VWAP";
+
+    fn init<T: OHLCV>(self, candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("VWAP period must be positive")));
+        }
+        Ok(Self::Instance { weighted_prices: RollingWindow::new(self.period), volumes: RollingWindow::new(self.period), vwap: typical_price(candle), prev_close: candle.close(), cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.period > 0
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (1, 1)
+    }
+}
+
+impl IndicatorInstance for VWAPInstance {
+    type Config = VwapConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let volume = candle.volume().max(0.);
+        self.weighted_prices.push(typical_price(candle) * volume);
+        self.volumes.push(volume);
+
+        let prev_vwap = self.vwap;
+        if self.volumes.sum() > 0. {
+            self.vwap = self.weighted_prices.sum() / self.volumes.sum();
+        }
+
+        let action = if self.prev_close <= prev_vwap && candle.close() > self.vwap {
+            Action::Buy(1)
+        } else if self.prev_close >= prev_vwap && candle.close() < self.vwap {
+            Action::Sell(1)
+        } else {
+            Action::None
+        };
+        self.prev_close = candle.close();
+
+        IndicatorResult::new(&[self.vwap], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for VWAPInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{anyhow, Result};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))
+    }
+
+    fn create_kline(price: f64, volume: f64) -> Result<BinanceKline> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        Ok(BinanceKline { start_time: timestamp, open: price, close: price, high: price, low: price, volume, end_time: timestamp })
+    }
+
+    #[test]
+    fn test_zero_volume_candle_carries_vwap_forward() -> Result<()> {
+        let first_kline = create_kline(100.0, 10.0)?;
+        let mut vwap = VwapConfig { period: 1 }.init(&first_kline)?;
+
+        let result = vwap.next_binance_kline(&first_kline);
+        assert_eq!(result.values()[0], 100.0);
+
+        let result = vwap.next_binance_kline(&create_kline(120.0, 0.0)?);
+        assert!(!result.values()[0].is_nan());
+        assert_eq!(result.values()[0], 100.0);
+
+        let result = vwap.next_binance_kline(&create_kline(110.0, 5.0)?);
+        assert_eq!(result.values()[0], 110.0);
+
+        Ok(())
+    }
+}
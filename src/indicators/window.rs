@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone)]
+pub struct RollingWindow {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+}
+
+impl RollingWindow {
+    pub fn new(period: usize) -> Self {
+        Self { period, values: VecDeque::with_capacity(period + 1), sum: 0. }
+    }
+
+    pub fn push(&mut self, value: f64) -> Option<f64> {
+        self.values.push_back(value);
+        self.sum += value;
+        if self.values.len() > self.period {
+            let evicted = self.values.pop_front()?;
+            self.sum -= evicted;
+            return Some(evicted);
+        }
+        None
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.period
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.values.is_empty() {
+            return None;
+        }
+        Some(self.sum / self.values.len() as f64)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.values.iter().copied().reduce(f64::max)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.values.iter().copied().reduce(f64::min)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.values.iter()
+    }
+}
@@ -0,0 +1,157 @@
+use crate::data::BinanceKline;
+use crate::indicators::{BinanceIndicatorInstance, RollingWindow};
+use yata::core::{Action, Error, IndicatorResult, OHLCV};
+use yata::prelude::*;
+
+// Linearly weighted moving average, the newest price weighs `period` and the oldest 1
+#[derive(Debug, Clone)]
+pub struct Wma {
+    window: RollingWindow,
+    weighted_sum: f64,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        Self { window: RollingWindow::new(period), weighted_sum: 0. }
+    }
+
+    // Every held price loses one weight step, which removes the plain sum, and the new price enters at full weight
+    pub fn next(&mut self, value: f64) -> Option<f64> {
+        let period = self.window.len() + usize::from(!self.window.is_full());
+        if self.window.is_full() {
+            self.weighted_sum -= self.window.sum();
+        }
+        self.weighted_sum += period as f64 * value;
+        self.window.push(value);
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        if !self.window.is_full() {
+            return None;
+        }
+        let n = self.window.len() as f64;
+        Some(self.weighted_sum / (n * (n + 1.) / 2.))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WmaConfig {
+    pub short: usize,
+    pub long: usize,
+}
+
+impl Default for WmaConfig {
+    fn default() -> Self {
+        Self { short: 10, long: 30 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WMAInstance {
+    cfg: WmaConfig,
+    short_wma: Wma,
+    long_wma: Wma,
+    prev_spread: Option<f64>,
+}
+
+impl IndicatorConfig for WmaConfig {
+    type Instance = WMAInstance;
+
+    const NAME: &'static str = "WMA";
+
+    fn init<T: OHLCV>(self, _candle: &T) -> Result<Self::Instance, Error> {
+        if !self.validate() {
+            return Err(Error::Other(String::from("WMA periods must be positive and short shorter than long")));
+        }
+        Ok(Self::Instance { short_wma: Wma::new(self.short), long_wma: Wma::new(self.long), prev_spread: None, cfg: self })
+    }
+    fn validate(&self) -> bool {
+        self.short > 0 && self.short < self.long
+    }
+    fn set(&mut self, _name: &str, _value: String) -> Result<(), Error> {
+        Ok(())
+    }
+    fn size(&self) -> (u8, u8) {
+        (2, 1)
+    }
+}
+
+impl IndicatorInstance for WMAInstance {
+    type Config = WmaConfig;
+
+    fn config(&self) -> &Self::Config {
+        &self.cfg
+    }
+
+    fn next<T: OHLCV>(&mut self, candle: &T) -> IndicatorResult {
+        let short = self.short_wma.next(candle.close());
+        let (Some(short), Some(long)) = (short, self.long_wma.next(candle.close())) else {
+            return IndicatorResult::new(&[], &[Action::None]);
+        };
+
+        let spread = short - long;
+        let action = match self.prev_spread {
+            Some(prev) if prev <= 0. && spread > 0. => Action::Buy(1),
+            Some(prev) if prev >= 0. && spread < 0. => Action::Sell(1),
+            _ => Action::None,
+        };
+        self.prev_spread = Some(spread);
+
+        IndicatorResult::new(&[short, long], &[action])
+    }
+}
+
+impl BinanceIndicatorInstance for WMAInstance {
+    fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
+        self.next(candle)
+    }
+
+    fn warm_up_period(&self) -> usize {
+        self.cfg.long
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_wma_responds_faster_than_sma_to_step() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let closes: Vec<f64> = (0..40).map(|i| if i < 20 { 100.0 } else { 110.0 }).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+
+        let mut wma = Wma::new(10);
+        let mut sma = RollingWindow::new(10);
+        for (i, close) in closes.iter().enumerate() {
+            let wma_value = wma.next(*close);
+            sma.push(*close);
+            let Some(wma_value) = wma_value else {
+                continue;
+            };
+            let sma_value = sma.mean().ok_or(anyhow!("No SMA"))?;
+            if (20..29).contains(&i) {
+                assert!(wma_value > sma_value);
+            } else {
+                assert!((wma_value - sma_value).abs() < 1e-9);
+            }
+        }
+        // One candle after the step the newest price already carries 10 of the 55 weights
+        let mut wma = Wma::new(10);
+        closes[..21].iter().for_each(|close| {
+            wma.next(*close);
+        });
+        assert!((wma.value().ok_or(anyhow!("No WMA"))? - (100.0 + 10.0 * 10.0 / 55.0)).abs() < 1e-9);
+
+        let mut crossover = WmaConfig { short: 3, long: 10 }.init(&klines[0])?;
+        let signals: Vec<(usize, Action)> = klines.iter().map(|kline| crossover.next_binance_kline(kline).signals()[0]).enumerate().filter(|(_, action)| *action != Action::None).collect();
+        assert_eq!(signals, vec![(20, Action::Buy(1))]);
+        assert!(WmaConfig { short: 10, long: 10 }.init(&klines[0]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+pub mod account;
+pub mod backtest;
+pub mod cli;
+pub mod data;
+pub mod error;
+pub mod indicators;
+pub mod metrics;
+pub mod report;
+mod rng;
+pub mod traders;
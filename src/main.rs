@@ -1,170 +1,82 @@
-mod account;
-mod data;
-mod indicators;
-mod traders;
-
-use account::{Account, Position};
 use chrono::{Duration, NaiveDate, Utc};
-use data::{get_kline_data, BinanceKline};
-use traders::{DCATrader, GenericTrader, HODLTrader, MACDTrader, SMA2Trader, SMATrader, StakeSize, TradingFee};
+use crypto_strategy_analysis::account::Account;
+use crypto_strategy_analysis::backtest::StrategyRegistry;
+use crypto_strategy_analysis::cli::parse_args;
+use crypto_strategy_analysis::data::{build_client, data_quality_report, BinanceKline, HttpConfig, Interval, KlineCache, SourcePolicy, Symbol, BINANCE_DATA_URL};
+use crypto_strategy_analysis::indicators::RsiConfig;
+use crypto_strategy_analysis::metrics::Annualisation;
+use crypto_strategy_analysis::report::{round_trip_report, write_round_trips_csv, write_trades_csv, BacktestReport};
+use crypto_strategy_analysis::traders::{CashTrader, DCATrader, HODLTrader, MACDTrader, MacdConfig, RSITrader, SMA2Trader, SMATrader, StakeSize, TradingFee};
 
 use env_logger::Env;
 use log::info;
 
 use anyhow::{anyhow, Result};
 
-use std::sync::Arc;
-use std::thread;
+use std::fs;
+use std::path::Path;
 
 use my_macros::log_duration;
 
 #[log_duration]
-async fn download_kline() -> Result<Vec<BinanceKline>> {
+async fn download_kline(symbol: &Symbol, interval: Interval, end_date: Option<NaiveDate>, cache_dir: &Path) -> Result<Vec<BinanceKline>> {
     let start_date = NaiveDate::from_ymd_opt(2024, 1, 1).ok_or(anyhow!("Invalid start date"))?;
-    let end_date = Utc::now().naive_utc() - Duration::days(1);
-    let end_date = end_date.date();
-    let symbol = "ETHUSDT";
-    let interval = "1h";
-    info!("Download data from binance for [{symbol}/{interval}] from [{start_date}] to [{end_date}]");
-    let klines = get_kline_data(symbol, interval, start_date, end_date).await?;
+    let end_date = end_date.unwrap_or_else(|| (Utc::now().naive_utc() - Duration::days(1)).date());
+    info!("Download data from binance for [{symbol} {interval}] from [{start_date}] to [{end_date}]");
+    let client = build_client(&HttpConfig::default())?;
+    info!("Caching downloaded archives in [{}]", cache_dir.display());
+    let klines = KlineCache::new(BINANCE_DATA_URL, cache_dir).get_kline_data(&client, symbol, interval, start_date, end_date, SourcePolicy::default()).await?;
     info!("Downloaded [{}] klines", klines.len());
     Ok(klines)
 }
 
-fn initialise_account(klines: &[BinanceKline], name: &str) -> Result<Account> {
-    info!("Setting up account {name}");
-    let first_kline = klines.first().ok_or(anyhow!("No klines fetched"))?;
-    let start_time = first_kline.start_time;
+#[log_duration]
+async fn backtest(klines: Vec<BinanceKline>) -> Result<Vec<(String, Result<Account>)>> {
+    let registry = StrategyRegistry::default()
+        .register("MACD", |klines| MACDTrader::new(klines, TradingFee::PercentageFee(0.005), StakeSize::FixPercentage(1.), MacdConfig::default()))
+        .register("HODL", |klines| HODLTrader::new(klines, TradingFee::PercentageFee(0.005), StakeSize::FixPercentage(1.)))
+        .register("Cash", CashTrader::new)
+        .register("DCA", |klines| DCATrader::new(klines, TradingFee::PercentageFee(0.005), StakeSize::FixAmount(100.0)))
+        .register("SMA", |klines| SMATrader::new(klines, TradingFee::PercentageFee(0.005), StakeSize::FixAmount(100.0), 1, 2))
+        .register("SMA2", |klines| SMA2Trader::new(klines, TradingFee::PercentageFee(0.005), StakeSize::FixAmount(100.0), 1, 2))
+        .register("RSI", |klines| RSITrader::new(klines, TradingFee::PercentageFee(0.005), StakeSize::FixPercentage(1.), RsiConfig::default()));
+    info!("Running backtests for {}", registry.names().join(", "));
+
+    // Each strategy gets its own thread, run_all blocks until all of them finish so keep it off the async runtime
     let start_fund = 1000.0;
-    let start_position = Position { quantity: 0.0, cost: 0.0 };
-    let account = Account::new(start_fund, start_position, start_time);
-    Ok(account)
-}
-
-fn initialise_macd_trader(klines: &[BinanceKline]) -> Result<MACDTrader> {
-    info!("Setting up MACD trader");
-    let stake_size = StakeSize::FixPercentage(1.);
-    let trading_fee = TradingFee::PercentageFee(0.005);
-    let trader = MACDTrader::new(klines, trading_fee, stake_size)?;
-    Ok(trader)
-}
-
-fn initialise_hodl_trader(klines: &[BinanceKline]) -> Result<HODLTrader> {
-    info!("Setting up HODL trader");
-    let trading_fee = TradingFee::PercentageFee(0.005);
-    let trader = HODLTrader::new(klines, trading_fee)?;
-    Ok(trader)
-}
-
-fn initialise_dca_trader(klines: &[BinanceKline]) -> Result<DCATrader> {
-    info!("Setting up DCA trader");
-    let trading_fee = TradingFee::PercentageFee(0.005);
-    let trader = DCATrader::new(klines, trading_fee)?;
-    Ok(trader)
-}
-
-fn initialise_sma_trader(klines: &[BinanceKline]) -> Result<SMATrader> {
-    info!("Setting up SMA trader");
-    let trading_fee = TradingFee::PercentageFee(0.005);
-    let trader = SMATrader::new(klines, trading_fee)?;
-    Ok(trader)
+    let results = tokio::task::spawn_blocking(move || registry.run_all(&klines, start_fund)).await??;
+    Ok(results.into_iter().map(|(name, account)| (name, account.map_err(anyhow::Error::from))).collect())
 }
 
-fn initialise_sma2_trader(klines: &[BinanceKline]) -> Result<SMA2Trader> {
-    info!("Setting up SMA2 trader");
-    let trading_fee = TradingFee::PercentageFee(0.005);
-    let trader = SMA2Trader::new(klines, trading_fee)?;
-    Ok(trader)
-}
-
-fn loop_kline<T>(trader: &mut T, account: &mut Account, name: &str, klines: &[BinanceKline]) -> Result<()>
-where
-    T: GenericTrader,
-{
-    info!("Running backtest {name}");
-    for kline in klines {
-        trader.next_trade_session(account, kline)?;
-        account.mark_to_market(kline.end_time, kline.close)?;
-    }
-
+fn write_trade_logs(output_dir: &Path, symbol: &Symbol, name: &str, account: &Account) -> Result<()> {
+    fs::create_dir_all(output_dir)?;
+    let pair = symbol.pair();
+    let trades_path = output_dir.join(format!("{pair}_{name}_trades.csv"));
+    let round_trips_path = output_dir.join(format!("{pair}_{name}_round_trips.csv"));
+    write_trades_csv(&trades_path, &account.trade_history)?;
+    write_round_trips_csv(&round_trips_path, &round_trip_report(&account.trade_history))?;
+    info!("Wrote {symbol} {name} fills to [{}] and round trips to [{}]", trades_path.display(), round_trips_path.display());
     Ok(())
 }
 
-#[log_duration]
-async fn backtest_macd(klines: Arc<Vec<BinanceKline>>, name: &str) -> Result<Account> {
-    let mut account = initialise_account(&klines, name)?;
-    let mut trader = initialise_macd_trader(&klines)?;
-    info!("MACD thread id: {:?}", thread::current().id());
-    loop_kline(&mut trader, &mut account, name, &klines)?;
-    Ok(account)
-}
-
-#[log_duration]
-async fn backtest_hodl(klines: Arc<Vec<BinanceKline>>, name: &str) -> Result<Account> {
-    let mut account = initialise_account(&klines, name)?;
-    let mut trader = initialise_hodl_trader(&klines)?;
-    info!("HODL thread id: {:?}", thread::current().id());
-    loop_kline(&mut trader, &mut account, name, &klines)?;
-    Ok(account)
-}
-
-#[log_duration]
-async fn backtest_dca(klines: Arc<Vec<BinanceKline>>, name: &str) -> Result<Account> {
-    let mut account = initialise_account(&klines, name)?;
-    let mut trader = initialise_dca_trader(&klines)?;
-    info!("DCA thread id: {:?}", thread::current().id());
-    loop_kline(&mut trader, &mut account, name, &klines)?;
-    Ok(account)
-}
-
-#[log_duration]
-async fn backtest_sma(klines: Arc<Vec<BinanceKline>>, name: &str) -> Result<Account> {
-    let mut account = initialise_account(&klines, name)?;
-    let mut trader = initialise_sma_trader(&klines)?;
-    info!("SMA thread id: {:?}", thread::current().id());
-    loop_kline(&mut trader, &mut account, name, &klines)?;
-    Ok(account)
-}
-
-#[log_duration]
-async fn backtest_sma2(klines: Arc<Vec<BinanceKline>>, name: &str) -> Result<Account> {
-    let mut account = initialise_account(&klines, name)?;
-    let mut trader = initialise_sma2_trader(&klines)?;
-    info!("SMA2 thread id: {:?}", thread::current().id());
-    loop_kline(&mut trader, &mut account, name, &klines)?;
-    Ok(account)
-}
-
-async fn backtest(klines: Vec<BinanceKline>) -> Result<(Result<Account>, Result<Account>, Result<Account>, Result<Account>, Result<Account>)> {
-    info!("Main thread id: {:?}", thread::current().id());
-
-    let klines = Arc::new(klines);
-
-    let macd_account_handle = tokio::spawn(backtest_macd(Arc::clone(&klines), "MACD"));
-    let hodl_account_handle = tokio::spawn(backtest_hodl(Arc::clone(&klines), "HODL"));
-    let dca_account_handle = tokio::spawn(backtest_dca(Arc::clone(&klines), "DCA"));
-    let sma_account_handle = tokio::spawn(backtest_sma(Arc::clone(&klines), "SMA"));
-    let sma2_account_handle = tokio::spawn(backtest_sma2(Arc::clone(&klines), "SMA2"));
-
-    let (macd_account, hodl_account, dca_account, sma_account, sma2_account) = tokio::join!(macd_account_handle, hodl_account_handle, dca_account_handle, sma_account_handle, sma2_account_handle);
-
-    Ok((macd_account?, hodl_account?, dca_account?, sma_account?, sma2_account?))
-}
-
 #[tokio::main]
 pub async fn main() -> Result<()> {
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-
-    let klines = download_kline().await?;
-
-    let result = backtest(klines);
-    let (macd_account, hodl_account, dca_account, sma_account, sma2_account) = result.await?;
-
-    info!("MACD: {:?}", macd_account?.profit_and_loss_history.last().ok_or(anyhow!("No pnl history for MACD"))?);
-    info!("HODL: {:?}", hodl_account?.profit_and_loss_history.last().ok_or(anyhow!("No pnl history for HODL"))?);
-    info!("DCA : {:?}", dca_account?.profit_and_loss_history.last().ok_or(anyhow!("No pnl history for DCA"))?);
-    info!("SMA : {:?}", sma_account?.profit_and_loss_history.last().ok_or(anyhow!("No pnl history for SMA"))?);
-    info!("SMA2 : {:?}", sma2_account?.profit_and_loss_history.last().ok_or(anyhow!("No pnl history for SMA2"))?);
+    let options = parse_args(std::env::args().skip(1))?;
+    env_logger::Builder::from_env(Env::default().default_filter_or(options.level_filter().to_string())).init();
+
+    let cache_dir = options.cache_dir.unwrap_or_else(KlineCache::default_dir);
+    let symbol: Symbol = "ETHUSDT".parse()?;
+    let interval = Interval::OneHour;
+    let klines = download_kline(&symbol, interval, options.end_date, &cache_dir).await?;
+    println!("Data quality: {}", data_quality_report(&klines));
+
+    for (name, account) in backtest(klines).await? {
+        let account = account?;
+        println!("{}", BacktestReport::from_account(&symbol, &name, &account, Annualisation::FromInterval(interval))?);
+        if let Some(output_dir) = &options.output_dir {
+            write_trade_logs(output_dir, &symbol, &name, &account)?;
+        }
+    }
 
     Ok(())
 }
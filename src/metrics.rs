@@ -0,0 +1,365 @@
+use crate::account::{TimeValue, Trade};
+use crate::data::{BinanceKline, Interval};
+use chrono::{Datelike, NaiveDateTime};
+use std::collections::HashMap;
+
+mod quantile;
+pub use quantile::P2Quantile;
+
+pub fn smooth_equity(history: &[TimeValue], period: usize) -> Vec<(NaiveDateTime, f64)> {
+    let alpha = 2. / (period.max(1) as f64 + 1.);
+    let mut smoothed = Vec::with_capacity(history.len());
+    let mut ema: Option<f64> = None;
+    for time_value in history {
+        let value = match ema {
+            Some(prev) => prev + alpha * (time_value.equity - prev),
+            None => time_value.equity,
+        };
+        ema = Some(value);
+        smoothed.push((time_value.timestamp, value));
+    }
+    smoothed
+}
+
+/// Number of return periods per year used to annualise metrics.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Annualisation {
+    /// Derive from the candle interval of the data
+    FromInterval(Interval),
+    /// Use an explicit number of periods, overriding the interval
+    Periods(f64),
+}
+
+impl Annualisation {
+    pub fn periods_per_year(&self) -> f64 {
+        match self {
+            Annualisation::FromInterval(interval) => interval.periods_per_year(),
+            Annualisation::Periods(periods) => *periods,
+        }
+    }
+}
+
+// Per-period (strategy, benchmark) returns over timestamps present in both curves
+fn aligned_returns(strategy: &[TimeValue], benchmark: &[TimeValue]) -> Vec<(f64, f64)> {
+    let benchmark_equity: HashMap<NaiveDateTime, f64> = benchmark.iter().map(|time_value| (time_value.timestamp, time_value.equity)).collect();
+    let aligned: Vec<(f64, f64)> = strategy.iter().filter_map(|time_value| benchmark_equity.get(&time_value.timestamp).map(|equity| (time_value.equity, *equity))).collect();
+
+    aligned.windows(2).filter(|pair| pair[0].0 != 0. && pair[0].1 != 0.).map(|pair| (pair[1].0 / pair[0].0 - 1., pair[1].1 / pair[0].1 - 1.)).collect()
+}
+
+pub fn information_ratio(strategy: &[TimeValue], benchmark: &[TimeValue], annualisation: Annualisation) -> f64 {
+    let active_returns: Vec<f64> = aligned_returns(strategy, benchmark).iter().map(|(strategy_return, benchmark_return)| strategy_return - benchmark_return).collect();
+    if active_returns.len() < 2 {
+        return 0.;
+    }
+
+    let count = active_returns.len() as f64;
+    let mean = active_returns.iter().sum::<f64>() / count;
+    let tracking_error = (active_returns.iter().map(|active| (active - mean).powi(2)).sum::<f64>() / (count - 1.)).sqrt();
+    if tracking_error == 0. {
+        return 0.;
+    }
+
+    mean / tracking_error * annualisation.periods_per_year().sqrt()
+}
+
+/// Regression slope of strategy returns on benchmark returns, aligned by timestamp.
+/// Returns NaN when fewer than two returns overlap or the benchmark never moves, as beta is undefined there.
+pub fn beta(strategy: &[TimeValue], benchmark: &[TimeValue]) -> f64 {
+    let returns = aligned_returns(strategy, benchmark);
+    if returns.len() < 2 {
+        return f64::NAN;
+    }
+
+    let count = returns.len() as f64;
+    let mean_strategy = returns.iter().map(|(strategy_return, _)| strategy_return).sum::<f64>() / count;
+    let mean_benchmark = returns.iter().map(|(_, benchmark_return)| benchmark_return).sum::<f64>() / count;
+    let covariance: f64 = returns.iter().map(|(strategy_return, benchmark_return)| (strategy_return - mean_strategy) * (benchmark_return - mean_benchmark)).sum();
+    let variance: f64 = returns.iter().map(|(_, benchmark_return)| (benchmark_return - mean_benchmark).powi(2)).sum();
+    if variance == 0. {
+        return f64::NAN;
+    }
+
+    covariance / variance
+}
+
+fn pearson(pairs: &[(f64, f64)]) -> f64 {
+    if pairs.len() < 2 {
+        return f64::NAN;
+    }
+    let count = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let covariance: f64 = pairs.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance_x: f64 = pairs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let variance_y: f64 = pairs.iter().map(|(_, y)| (y - mean_y).powi(2)).sum();
+    covariance / (variance_x * variance_y).sqrt()
+}
+
+fn aligned_log_returns(klines: &[BinanceKline], other_closes: &HashMap<NaiveDateTime, f64>) -> Vec<(f64, f64)> {
+    let aligned: Vec<(f64, f64)> = klines.iter().filter_map(|kline| other_closes.get(&kline.start_time).map(|close| (kline.close, *close))).collect();
+    aligned.windows(2).filter(|pair| pair.iter().all(|(x, y)| *x > 0. && *y > 0.)).map(|pair| ((pair[1].0 / pair[0].0).ln(), (pair[1].1 / pair[0].1).ln())).collect()
+}
+
+/// Pearson correlation of log-returns for every pair of symbols, aligned on the timestamps both series share.
+/// Pairs with fewer than two overlapping returns are NaN.
+pub fn correlation_matrix(series: &[(String, Vec<BinanceKline>)]) -> Vec<Vec<f64>> {
+    let closes: Vec<HashMap<NaiveDateTime, f64>> = series.iter().map(|(_, klines)| klines.iter().map(|kline| (kline.start_time, kline.close)).collect()).collect();
+    series.iter().map(|(_, klines)| closes.iter().map(|other_closes| pearson(&aligned_log_returns(klines, other_closes))).collect()).collect()
+}
+
+/// Annualised mean over standard deviation of the per-mark equity returns, with no risk-free rate.
+/// None with fewer than two returns or when returns never vary.
+pub fn sharpe_ratio(history: &[TimeValue], annualisation: Annualisation) -> Option<f64> {
+    let returns: Vec<f64> = history.windows(2).filter(|pair| pair[0].equity != 0.).map(|pair| pair[1].equity / pair[0].equity - 1.).collect();
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let count = returns.len() as f64;
+    let mean = returns.iter().sum::<f64>() / count;
+    let stddev = (returns.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (count - 1.)).sqrt();
+    if stddev == 0. {
+        return None;
+    }
+    Some(mean / stddev * annualisation.periods_per_year().sqrt())
+}
+
+/// Part of the equity curve `max_drawdown` is measured over.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DrawdownWindow {
+    /// Every mark, including an idle warm-up before the first trade
+    #[default]
+    Overall,
+    /// From the mark of the first trade to the last mark, zero when nothing was traded
+    InTrade,
+}
+
+/// Largest peak-to-trough decline of equity as a fraction of the peak, zero for a curve that never falls.
+pub fn max_drawdown(history: &[TimeValue], trades: &[Trade], window: DrawdownWindow) -> f64 {
+    let start = match window {
+        DrawdownWindow::Overall => 0,
+        DrawdownWindow::InTrade => match trades.first() {
+            Some(trade) => history.partition_point(|time_value| time_value.timestamp < trade.timestamp),
+            None => return 0.,
+        },
+    };
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.;
+    for time_value in &history[start..] {
+        peak = peak.max(time_value.equity);
+        if peak > 0. {
+            drawdown = drawdown.max(1. - time_value.equity / peak);
+        }
+    }
+    drawdown
+}
+
+/// Calendar bucket of `periodic_returns`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Granularity {
+    #[default]
+    Month,
+    Year,
+}
+
+impl Granularity {
+    fn label(&self, timestamp: NaiveDateTime) -> String {
+        match self {
+            Granularity::Month => format!("{}-{:02}", timestamp.year(), timestamp.month()),
+            Granularity::Year => timestamp.year().to_string(),
+        }
+    }
+}
+
+/// Return of every calendar month or year, labelled `YYYY-MM` or `YYYY`, measured from the previous period's last mark.
+/// A partial leading period is measured from its first mark and a partial trailing one up to its last mark.
+pub fn periodic_returns(history: &[TimeValue], granularity: Granularity) -> Vec<(String, f64)> {
+    let mut returns: Vec<(String, f64)> = Vec::new();
+    let mut base = match history.first() {
+        Some(time_value) => time_value.equity,
+        None => return returns,
+    };
+    let mut period: Option<(String, f64)> = None;
+    for time_value in history {
+        let label = granularity.label(time_value.timestamp);
+        match period.as_mut() {
+            Some((current, last_equity)) if *current == label => *last_equity = time_value.equity,
+            _ => {
+                if let Some((finished, last_equity)) = period.take() {
+                    returns.push((finished, if base != 0. { last_equity / base - 1. } else { 0. }));
+                    base = last_equity;
+                }
+                period = Some((label, time_value.equity));
+            }
+        }
+    }
+    if let Some((finished, last_equity)) = period {
+        returns.push((finished, if base != 0. { last_equity / base - 1. } else { 0. }));
+    }
+    returns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::BuySellIndicator;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    fn create_history(equities: &[f64]) -> Result<Vec<TimeValue>> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        Ok(equities.iter().enumerate().map(|(i, equity)| TimeValue { timestamp: start_time + Duration::hours(i as i64), realised_pnl: 0., unrealised_pnl: 0., equity: *equity }).collect())
+    }
+
+    #[test]
+    fn test_sharpe_ratio_of_known_returns() -> Result<()> {
+        let history = create_history(&[100., 110., 99., 108.9])?;
+
+        // Returns 10%, -10%, 10%: mean 1/30, sample deviation sqrt(0.04 / 3)
+        let expected = (1. / 30.) / (0.04_f64 / 3.).sqrt() * 365_f64.sqrt();
+        let sharpe = sharpe_ratio(&history, Annualisation::FromInterval(Interval::OneDay)).ok_or(anyhow!("No Sharpe ratio"))?;
+        assert!((sharpe - expected).abs() < 1e-9);
+
+        let hourly = sharpe_ratio(&history, Annualisation::Periods(8760.)).ok_or(anyhow!("No Sharpe ratio"))?;
+        assert!((hourly - sharpe * (8760_f64 / 365.).sqrt()).abs() < 1e-9);
+
+        assert_eq!(sharpe_ratio(&history[..2], Annualisation::Periods(365.)), None);
+        assert_eq!(sharpe_ratio(&create_history(&[100., 110., 121.])?, Annualisation::Periods(365.)), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_trade_drawdown_skips_idle_warm_up() -> Result<()> {
+        // Idle at the starting equity, then the entry fee and a fall while in the trade
+        let history = create_history(&[1000., 1000., 1000., 990., 900.])?;
+        let trades = vec![Trade { timestamp: history[3].timestamp, buy_sell_indicator: BuySellIndicator::Buy, quantity: 1.0, price: 990.0, intended_price: 990.0, fee: 10.0 }];
+        let overall = max_drawdown(&history, &trades, DrawdownWindow::Overall);
+        let in_trade = max_drawdown(&history, &trades, DrawdownWindow::InTrade);
+        assert!((overall - 0.1).abs() < 1e-12);
+        assert!((in_trade - (1. - 900. / 990.)).abs() < 1e-12);
+
+        assert_eq!(max_drawdown(&history, &[], DrawdownWindow::InTrade), 0.);
+        assert_eq!(max_drawdown(&create_history(&[100., 110., 120.])?, &[], DrawdownWindow::Overall), 0.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_smooth_equity_dampens_and_lags_spike() -> Result<()> {
+        let history = create_history(&[100., 100., 100., 200., 100., 100.])?;
+        let smoothed = smooth_equity(&history, 3);
+
+        assert_eq!(smoothed.len(), history.len());
+        assert_eq!(smoothed[2].1, 100.);
+        assert_eq!(smoothed[3].1, 150.);
+        assert!(smoothed[4].1 > 100.);
+        assert!(smoothed[5].1 < smoothed[4].1);
+        assert_eq!(smoothed[3].0, history[3].timestamp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_information_ratio_rewards_consistent_outperformance() -> Result<()> {
+        let benchmark_returns = [0.02, -0.01, 0.03, -0.02, 0.01, 0.0, -0.03, 0.02];
+        let mut benchmark_equities = vec![1000.];
+        let mut strategy_equities = vec![1000.];
+        for (i, benchmark_return) in benchmark_returns.iter().enumerate() {
+            let active = if i % 2 == 0 { 0.0012 } else { 0.0008 };
+            benchmark_equities.push(benchmark_equities[i] * (1. + benchmark_return));
+            strategy_equities.push(strategy_equities[i] * (1. + benchmark_return + active));
+        }
+        // Unmatched benchmark point is skipped
+        benchmark_equities.push(500.);
+
+        let strategy = create_history(&strategy_equities)?;
+        let benchmark = create_history(&benchmark_equities)?;
+
+        let ratio = information_ratio(&strategy, &benchmark, Annualisation::FromInterval(Interval::OneDay));
+        assert!(ratio > 50.);
+        assert!(information_ratio(&benchmark, &strategy, Annualisation::Periods(365.)) < -50.);
+        assert_eq!(information_ratio(&benchmark, &benchmark, Annualisation::FromInterval(Interval::OneDay)), 0.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_periodic_returns_by_month_and_year() -> Result<()> {
+        let mut history = Vec::new();
+        let mut equity = 1000.;
+        for month in 0..24 {
+            let (year, month) = (2023 + month / 12, month % 12 + 1);
+            if month != 1 || year != 2023 {
+                equity *= if month % 3 == 0 { 0.98 } else { 1.03 };
+            }
+            let timestamp = NaiveDate::from_ymd_opt(year, month as u32, 15).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+            history.push(TimeValue { timestamp, realised_pnl: 0., unrealised_pnl: 0., equity });
+        }
+
+        let monthly = periodic_returns(&history, Granularity::Month);
+        assert_eq!(monthly.len(), 24);
+        assert_eq!(monthly[0], (String::from("2023-01"), 0.));
+        assert_eq!(monthly[1].0, "2023-02");
+        assert!((monthly[1].1 - 0.03).abs() < 1e-9);
+        assert!((monthly[2].1 + 0.02).abs() < 1e-9);
+        assert_eq!(monthly[23].0, "2024-12");
+
+        let yearly = periodic_returns(&history, Granularity::Year);
+        assert_eq!(yearly.len(), 2);
+        assert_eq!(yearly[0].0, "2023");
+        assert!((yearly[0].1 - (1.03_f64.powi(7) * 0.98_f64.powi(4) - 1.)).abs() < 1e-9);
+        assert!((yearly[1].1 - (1.03_f64.powi(8) * 0.98_f64.powi(4) - 1.)).abs() < 1e-9);
+        assert!(periodic_returns(&[], Granularity::Year).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_beta_of_half_exposure() -> Result<()> {
+        let benchmark_returns = [0.02, -0.01, 0.03, -0.02, 0.01, 0.0, -0.03, 0.02];
+        let mut benchmark_equities = vec![1000.];
+        let mut strategy_equities = vec![1000.];
+        for (i, benchmark_return) in benchmark_returns.iter().enumerate() {
+            benchmark_equities.push(benchmark_equities[i] * (1. + benchmark_return));
+            strategy_equities.push(strategy_equities[i] * (1. + 0.5 * benchmark_return));
+        }
+
+        let strategy = create_history(&strategy_equities)?;
+        let benchmark = create_history(&benchmark_equities)?;
+
+        assert!((beta(&strategy, &benchmark) - 0.5).abs() < 1e-9);
+        assert!((beta(&benchmark, &benchmark) - 1.0).abs() < 1e-9);
+        assert!(beta(&strategy[..2], &benchmark).is_nan());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_correlation_matrix() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let base: Vec<f64> = (0..50).map(|i| 100.0 + 10.0 * (i as f64 / 3.0).sin()).collect();
+        let doubled: Vec<f64> = base.iter().map(|close| 2.0 * close).collect();
+        let inverse: Vec<f64> = base.iter().map(|close| 10000.0 / close).collect();
+
+        let series = vec![
+            (String::from("BASE"), klines_from_closes(&base, start_time, Duration::hours(1))),
+            (String::from("DOUBLED"), klines_from_closes(&doubled[10..], start_time + Duration::hours(10), Duration::hours(1))),
+            (String::from("INVERSE"), klines_from_closes(&inverse, start_time, Duration::hours(1))),
+            (String::from("LATER"), klines_from_closes(&base[..5], start_time + Duration::days(30), Duration::hours(1))),
+        ];
+
+        let matrix = correlation_matrix(&series);
+
+        assert!((matrix[0][0] - 1.0).abs() < 1e-9);
+        assert!((matrix[0][1] - 1.0).abs() < 1e-9);
+        assert!((matrix[1][0] - 1.0).abs() < 1e-9);
+        assert!((matrix[0][2] + 1.0).abs() < 1e-9);
+        assert!((matrix[1][2] + 1.0).abs() < 1e-9);
+        assert!(matrix[0][3].is_nan());
+
+        Ok(())
+    }
+}
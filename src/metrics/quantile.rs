@@ -0,0 +1,109 @@
+use crate::error::{Error, Result};
+
+// P² estimator (Jain & Chlamtac): tracks a single quantile with five markers instead of the full sample
+#[derive(Debug, Clone)]
+pub struct P2Quantile {
+    p: f64,
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    pub fn new(p: f64) -> Result<Self> {
+        if !(p > 0. && p < 1.) {
+            return Err(Error::InvalidConfig(format!("Quantile must be between 0 and 1, got {p}")));
+        }
+        Ok(Self { p, count: 0, heights: [0.; 5], positions: [1., 2., 3., 4., 5.], desired: [1., 1. + 2. * p, 1. + 4. * p, 3. + 2. * p, 5.], increments: [0., p / 2., p, (1. + p) / 2., 1.] })
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (0..4).find(|&i| value < self.heights[i + 1]).unwrap_or(3)
+        };
+
+        for position in self.positions.iter_mut().skip(cell + 1) {
+            *position += 1.;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let offset = self.desired[i] - self.positions[i];
+            if (offset >= 1. && self.positions[i + 1] - self.positions[i] > 1.) || (offset <= -1. && self.positions[i - 1] - self.positions[i] < -1.) {
+                let step = offset.signum();
+                let parabolic = self.parabolic(i, step);
+                self.heights[i] = if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] { parabolic } else { self.linear(i, step) };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    pub fn estimate(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            1..=4 => {
+                let mut sample = self.heights[..self.count].to_vec();
+                sample.sort_by(f64::total_cmp);
+                sample.get((self.p * (self.count - 1) as f64).round() as usize).copied()
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + step / (n[i + 1] - n[i - 1]) * ((n[i] - n[i - 1] + step) * (q[i + 1] - q[i]) / (n[i + 1] - n[i]) + (n[i + 1] - n[i] - step) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let neighbour = if step > 0. { i + 1 } else { i - 1 };
+        self.heights[i] + step * (self.heights[neighbour] - self.heights[i]) / (self.positions[neighbour] - self.positions[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::SplitMix64;
+
+    #[test]
+    fn test_p2_quantile_tracks_exact_quantile() -> Result<()> {
+        let mut rng = SplitMix64::new(42);
+        let sample: Vec<f64> = (0..5000).map(|_| rng.next_gaussian()).collect();
+
+        let mut estimator = P2Quantile::new(0.05)?;
+        for value in &sample {
+            estimator.push(*value);
+        }
+
+        let mut sorted = sample.clone();
+        sorted.sort_by(f64::total_cmp);
+        let exact = sorted[(0.05 * (sorted.len() - 1) as f64).round() as usize];
+        let estimate = estimator.estimate().ok_or(Error::NoData(String::from("No estimate")))?;
+
+        assert!((estimate - exact).abs() < 0.1);
+        assert!(P2Quantile::new(1.5).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+use crate::account::{Account, BuySellIndicator, Trade};
+use crate::data::Symbol;
+use crate::error::{Error, Result};
+use crate::metrics::{max_drawdown, sharpe_ratio, Annualisation, DrawdownWindow};
+use chrono::{Duration, NaiveDateTime};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, PartialEq)]
+pub struct RoundTrip {
+    pub entry_time: NaiveDateTime,
+    pub exit_time: NaiveDateTime,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub quantity: f64,
+    pub gross_pnl: f64,
+    pub fees: f64,
+    pub net_pnl: f64,
+    pub holding_duration: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BacktestReport {
+    pub symbol: Symbol,
+    pub name: String,
+    pub final_equity: f64,
+    pub trade_count: usize,
+    pub total_fees: f64,
+    pub total_slippage: f64,
+    /// Traded notional over average equity, how many times the capital was turned over
+    pub turnover: f64,
+    /// Turnover scaled to a 365 day year from the span of the equity history
+    pub annualised_turnover: f64,
+    /// Largest fall from an equity peak over the whole run, as a fraction
+    pub max_drawdown: f64,
+    /// Same from the first trade on, ignoring an idle warm-up
+    pub in_trade_max_drawdown: f64,
+    /// Annualised Sharpe ratio of the equity curve, None when returns never vary
+    pub sharpe_ratio: Option<f64>,
+}
+
+impl BacktestReport {
+    pub fn from_account(symbol: &Symbol, name: &str, account: &Account, annualisation: Annualisation) -> Result<Self> {
+        let history = &account.profit_and_loss_history;
+        let last_pnl = history.last().ok_or(Error::NoData(format!("No pnl history for {symbol} {name}")))?;
+        let avg_equity = history.iter().map(|time_value| time_value.equity).sum::<f64>() / history.len() as f64;
+        let turnover = turnover(&account.trade_history, avg_equity);
+        let span = last_pnl.timestamp - history[0].timestamp;
+        let annualised_turnover = if span > Duration::zero() { turnover * Duration::days(365).num_seconds() as f64 / span.num_seconds() as f64 } else { 0. };
+        Ok(Self {
+            symbol: symbol.clone(),
+            name: name.to_string(),
+            final_equity: last_pnl.equity,
+            trade_count: account.trade_history.len(),
+            total_fees: account.trade_history.iter().map(|trade| trade.fee).sum(),
+            total_slippage: account.trade_history.iter().map(Trade::slippage_cost).sum(),
+            turnover,
+            annualised_turnover,
+            max_drawdown: max_drawdown(history, &account.trade_history, DrawdownWindow::Overall),
+            in_trade_max_drawdown: max_drawdown(history, &account.trade_history, DrawdownWindow::InTrade),
+            sharpe_ratio: sharpe_ratio(history, annualisation),
+        })
+    }
+}
+
+// Quote amounts are shown with the precision of the quote asset, e.g. cents for USDT
+impl fmt::Display for BacktestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = self.symbol.quote_precision() as usize;
+        let quote = &self.symbol.quote;
+        write!(f, "{} {}: final equity {:.precision$} {quote}, {} trades", self.symbol, self.name, self.final_equity, self.trade_count)?;
+        write!(f, ", fees {:.precision$} {quote}, slippage {:.precision$} {quote}", self.total_fees, self.total_slippage)?;
+        write!(f, ", turnover {:.2}x ({:.2}x annualised)", self.turnover, self.annualised_turnover)?;
+        write!(f, ", max drawdown {:.2}% ({:.2}% in trade)", self.max_drawdown * 100., self.in_trade_max_drawdown * 100.)?;
+        match self.sharpe_ratio {
+            Some(sharpe_ratio) => write!(f, ", Sharpe {sharpe_ratio:.2}"),
+            None => write!(f, ", Sharpe n/a"),
+        }
+    }
+}
+
+/// Total traded notional, buys and sells alike, divided by the average equity; zero when there is no equity to turn over
+pub fn turnover(trades: &[Trade], avg_equity: f64) -> f64 {
+    if avg_equity <= 0. {
+        return 0.;
+    }
+    trades.iter().map(|trade| trade.quantity * trade.price).sum::<f64>() / avg_equity
+}
+
+struct OpenRoundTrip {
+    entry_time: NaiveDateTime,
+    /// Signed like the account position, negative while short
+    position: f64,
+    long: bool,
+    bought_quantity: f64,
+    bought_notional: f64,
+    sold_quantity: f64,
+    sold_notional: f64,
+    fees: f64,
+}
+
+impl OpenRoundTrip {
+    fn new(entry_time: NaiveDateTime, long: bool) -> Self {
+        Self { entry_time, position: 0., long, bought_quantity: 0., bought_notional: 0., sold_quantity: 0., sold_notional: 0., fees: 0. }
+    }
+
+    fn fill(&mut self, trade: &Trade) {
+        match trade.buy_sell_indicator {
+            BuySellIndicator::Buy => {
+                self.position += trade.quantity;
+                self.bought_quantity += trade.quantity;
+                self.bought_notional += trade.quantity * trade.price;
+            }
+            BuySellIndicator::Sell => {
+                self.position -= trade.quantity;
+                self.sold_quantity += trade.quantity;
+                self.sold_notional += trade.quantity * trade.price;
+            }
+        }
+        self.fees += trade.fee;
+    }
+
+    // A long enters on its buys and a short on its sells, either way the gross PnL is what was sold less what was bought
+    fn finish(self, exit_time: NaiveDateTime) -> RoundTrip {
+        let gross_pnl = self.sold_notional - self.bought_notional;
+        let ((entry_notional, entry_quantity), (exit_notional, exit_quantity)) = if self.long {
+            ((self.bought_notional, self.bought_quantity), (self.sold_notional, self.sold_quantity))
+        } else {
+            ((self.sold_notional, self.sold_quantity), (self.bought_notional, self.bought_quantity))
+        };
+        RoundTrip {
+            entry_time: self.entry_time,
+            exit_time,
+            entry_price: entry_notional / entry_quantity,
+            exit_price: exit_notional / exit_quantity,
+            quantity: entry_quantity,
+            gross_pnl,
+            fees: self.fees,
+            net_pnl: gross_pnl - self.fees,
+            holding_duration: exit_time - self.entry_time,
+        }
+    }
+}
+
+/// Rebuilds round trips from the fills, a trip opens on the first fill from flat, a buy for a long and a sell for a short, and closes once flat again.
+pub fn round_trip_report(trades: &[Trade]) -> Vec<RoundTrip> {
+    let mut round_trips = Vec::new();
+    let mut open_round_trip: Option<OpenRoundTrip> = None;
+
+    for trade in trades {
+        let mut round_trip = open_round_trip.take().unwrap_or_else(|| OpenRoundTrip::new(trade.timestamp, trade.buy_sell_indicator == BuySellIndicator::Buy));
+        round_trip.fill(trade);
+        if round_trip.position.abs() <= f64::EPSILON {
+            round_trips.push(round_trip.finish(trade.timestamp));
+        } else {
+            open_round_trip = Some(round_trip);
+        }
+    }
+
+    round_trips
+}
+
+/// Counts closed round trips by holding duration, each bucket keyed by its lower bound.
+/// Buckets run contiguously from zero to the longest hold, so empty ones show up as gaps in the distribution.
+pub fn holding_period_histogram(trades: &[Trade], bucket: Duration) -> Vec<(Duration, usize)> {
+    let bucket_seconds = bucket.num_seconds();
+    if bucket_seconds <= 0 {
+        return Vec::new();
+    }
+    let mut counts: Vec<usize> = Vec::new();
+    for round_trip in round_trip_report(trades) {
+        let index = (round_trip.holding_duration.num_seconds().max(0) / bucket_seconds) as usize;
+        if counts.len() <= index {
+            counts.resize(index + 1, 0);
+        }
+        counts[index] += 1;
+    }
+    counts.into_iter().enumerate().map(|(index, count)| (bucket * index as i32, count)).collect()
+}
+
+pub fn write_trades_csv(path: &Path, trades: &[Trade]) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "timestamp,side,quantity,price,fee")?;
+    for trade in trades {
+        writeln!(writer, "{},{:?},{},{},{}", trade.timestamp, trade.buy_sell_indicator, trade.quantity, trade.price, trade.fee)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn write_round_trips_csv(path: &Path, round_trips: &[RoundTrip]) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "entry_time,exit_time,entry_price,exit_price,quantity,gross_pnl,fees,net_pnl,holding_hours")?;
+    for round_trip in round_trips {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            round_trip.entry_time,
+            round_trip.exit_time,
+            round_trip.entry_price,
+            round_trip.exit_price,
+            round_trip.quantity,
+            round_trip.gross_pnl,
+            round_trip.fees,
+            round_trip.net_pnl,
+            round_trip.holding_duration.num_minutes() as f64 / 60.
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// Long format, one block per strategy, so it pivots cleanly in pandas
+pub fn write_combined_equity_csv(path: &Path, strategies: &[(String, &Account)]) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "strategy,timestamp,equity,realised_pnl,unrealised_pnl")?;
+    for (name, account) in strategies {
+        for time_value in &account.profit_and_loss_history {
+            writeln!(writer, "{},{},{},{},{}", name, time_value.timestamp, time_value.equity, time_value.realised_pnl, time_value.unrealised_pnl)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Position;
+    use crate::data::Interval;
+    use anyhow::{anyhow, Result};
+    use chrono::NaiveDate;
+
+    fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))
+    }
+
+    fn create_trade(timestamp: NaiveDateTime, buy_sell_indicator: BuySellIndicator, quantity: f64, price: f64) -> Trade {
+        Trade { timestamp, buy_sell_indicator, quantity, price, intended_price: price, fee: quantity }
+    }
+
+    #[test]
+    fn test_round_trip_report() -> Result<()> {
+        let trades = vec![
+            create_trade(create_timestamp(2024, 1, 1)?, BuySellIndicator::Buy, 1.0, 100.0),
+            create_trade(create_timestamp(2024, 1, 2)?, BuySellIndicator::Sell, 1.0, 110.0),
+            create_trade(create_timestamp(2024, 2, 1)?, BuySellIndicator::Buy, 2.0, 100.0),
+            create_trade(create_timestamp(2024, 2, 3)?, BuySellIndicator::Sell, 1.0, 120.0),
+            create_trade(create_timestamp(2024, 2, 5)?, BuySellIndicator::Sell, 1.0, 130.0),
+        ];
+
+        let round_trips = round_trip_report(&trades);
+
+        assert_eq!(
+            round_trips,
+            vec![
+                RoundTrip {
+                    entry_time: create_timestamp(2024, 1, 1)?,
+                    exit_time: create_timestamp(2024, 1, 2)?,
+                    entry_price: 100.0,
+                    exit_price: 110.0,
+                    quantity: 1.0,
+                    gross_pnl: 10.0,
+                    fees: 2.0,
+                    net_pnl: 8.0,
+                    holding_duration: Duration::days(1),
+                },
+                RoundTrip {
+                    entry_time: create_timestamp(2024, 2, 1)?,
+                    exit_time: create_timestamp(2024, 2, 5)?,
+                    entry_price: 100.0,
+                    exit_price: 125.0,
+                    quantity: 2.0,
+                    gross_pnl: 50.0,
+                    fees: 4.0,
+                    net_pnl: 46.0,
+                    holding_duration: Duration::days(4),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trips_follow_position_direction() -> Result<()> {
+        let start_time = create_timestamp(2024, 1, 1)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+        account.open(create_timestamp(2024, 1, 1)?, 1.0, 100.0, 0.0)?;
+        account.close(create_timestamp(2024, 1, 2)?, 1.0, 110.0, 0.0)?;
+        account.open_short(create_timestamp(2024, 1, 2)?, 2.0, 110.0, 0.0)?;
+        account.close_short(create_timestamp(2024, 1, 5)?, 2.0, 90.0, 0.0)?;
+        account.open(create_timestamp(2024, 1, 5)?, 1.0, 90.0, 0.0)?;
+        account.close(create_timestamp(2024, 1, 6)?, 1.0, 80.0, 0.0)?;
+
+        let round_trips = round_trip_report(&account.trade_history);
+
+        let summary: Vec<(f64, f64, f64, f64, Duration)> =
+            round_trips.iter().map(|round_trip| (round_trip.entry_price, round_trip.exit_price, round_trip.quantity, round_trip.gross_pnl, round_trip.holding_duration)).collect();
+        assert_eq!(summary, vec![(100.0, 110.0, 1.0, 10.0, Duration::days(1)), (110.0, 90.0, 2.0, 40.0, Duration::days(3)), (90.0, 80.0, 1.0, -10.0, Duration::days(1))]);
+        assert_eq!(holding_period_histogram(&account.trade_history, Duration::days(2)), vec![(Duration::zero(), 2), (Duration::days(2), 1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_holding_period_histogram() -> Result<()> {
+        let trades = vec![
+            create_trade(create_timestamp(2024, 1, 1)?, BuySellIndicator::Buy, 1.0, 100.0),
+            create_trade(create_timestamp(2024, 1, 2)?, BuySellIndicator::Sell, 1.0, 110.0),
+            create_trade(create_timestamp(2024, 1, 3)?, BuySellIndicator::Buy, 1.0, 100.0),
+            create_trade(create_timestamp(2024, 1, 4)?, BuySellIndicator::Sell, 1.0, 110.0),
+            create_trade(create_timestamp(2024, 1, 5)?, BuySellIndicator::Buy, 1.0, 100.0),
+            create_trade(create_timestamp(2024, 1, 15)?, BuySellIndicator::Sell, 1.0, 110.0),
+            create_trade(create_timestamp(2024, 2, 1)?, BuySellIndicator::Buy, 1.0, 100.0),
+        ];
+
+        let histogram = holding_period_histogram(&trades, Duration::days(5));
+
+        assert_eq!(histogram, vec![(Duration::zero(), 2), (Duration::days(5), 0), (Duration::days(10), 1)]);
+        assert!(holding_period_histogram(&trades, Duration::zero()).is_empty());
+        assert!(holding_period_histogram(&[], Duration::days(1)).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backtest_report_totals_fees_and_slippage() -> Result<()> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, timestamp);
+        account.open_at_fill(timestamp, 2.0, 100.0, 100.5, 0.2)?;
+        account.close_at_fill(timestamp, 1.0, 110.0, 109.0, 0.11)?;
+        account.close_at_fill(timestamp, 1.0, 120.0, 120.0, 0.12)?;
+
+        let report = BacktestReport::from_account(&"ETHUSDT".parse()?, "test", &account, Annualisation::FromInterval(Interval::OneDay))?;
+
+        assert_eq!(report.trade_count, 3);
+        assert!((report.total_fees - 0.43).abs() < 1e-9);
+        assert!((report.total_slippage - 2.0).abs() < 1e-9);
+        assert!(report.to_string().starts_with("ETH/USDT test: final equity "));
+        assert!(report.to_string().contains("3 trades, fees 0.43 USDT, slippage 2.00 USDT"));
+
+        let report = BacktestReport::from_account(&"SOLBTC".parse()?, "test", &account, Annualisation::FromInterval(Interval::OneDay))?;
+        assert!(report.to_string().contains("fees 0.43000000 BTC, slippage 2.00000000 BTC"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_turnover_of_known_trades() -> Result<()> {
+        let trades = vec![
+            create_trade(create_timestamp(2024, 1, 1)?, BuySellIndicator::Buy, 5.0, 100.0),
+            create_trade(create_timestamp(2024, 1, 2)?, BuySellIndicator::Sell, 5.0, 120.0),
+            create_trade(create_timestamp(2024, 1, 3)?, BuySellIndicator::Buy, 2.0, 150.0),
+        ];
+
+        assert_eq!(turnover(&trades, 1000.0), 1.4);
+        assert_eq!(turnover(&trades, 0.0), 0.0);
+        assert_eq!(turnover(&[], 1000.0), 0.0);
+
+        let start_time = create_timestamp(2024, 1, 1)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+        account.open(start_time, 5.0, 100.0, 0.0)?;
+        account.close(start_time, 5.0, 100.0, 0.0)?;
+        account.mark_to_market(start_time + Duration::days(73), 100.0)?;
+
+        let report = BacktestReport::from_account(&"ETHUSDT".parse()?, "test", &account, Annualisation::FromInterval(Interval::OneDay))?;
+        assert_eq!(report.turnover, 1.0);
+        assert_eq!(report.annualised_turnover, 5.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_round_trips_csv() -> Result<()> {
+        let trades = vec![
+            create_trade(create_timestamp(2024, 1, 1)?, BuySellIndicator::Buy, 1.0, 100.0),
+            create_trade(create_timestamp(2024, 1, 2)?, BuySellIndicator::Sell, 1.0, 110.0),
+            create_trade(create_timestamp(2024, 1, 3)?, BuySellIndicator::Sell, 2.0, 120.0),
+            create_trade(create_timestamp(2024, 1, 4)?, BuySellIndicator::Buy, 2.0, 100.0),
+        ];
+
+        let file = tempfile::NamedTempFile::new()?;
+        write_round_trips_csv(file.path(), &round_trip_report(&trades))?;
+        let content = std::fs::read_to_string(file.path())?;
+        let mut lines = content.lines();
+
+        assert_eq!(lines.next(), Some("entry_time,exit_time,entry_price,exit_price,quantity,gross_pnl,fees,net_pnl,holding_hours"));
+        assert_eq!(lines.next(), Some("2024-01-01 00:00:00,2024-01-02 00:00:00,100,110,1,10,2,8,24"));
+        assert_eq!(lines.count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_combined_equity_csv() -> Result<()> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        let mut first = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, timestamp);
+        first.mark_to_market(create_timestamp(2024, 1, 2)?, 100.0)?;
+        first.mark_to_market(create_timestamp(2024, 1, 3)?, 100.0)?;
+        let mut second = Account::new(500.0, Position { quantity: 0.0, cost: 0.0 }, timestamp);
+        second.mark_to_market(create_timestamp(2024, 1, 2)?, 100.0)?;
+
+        let file = tempfile::NamedTempFile::new()?;
+        write_combined_equity_csv(file.path(), &[(String::from("MACD"), &first), (String::from("HODL"), &second)])?;
+        let content = std::fs::read_to_string(file.path())?;
+        let mut lines = content.lines();
+
+        assert_eq!(lines.next(), Some("strategy,timestamp,equity,realised_pnl,unrealised_pnl"));
+        let strategies: Vec<&str> = lines.filter_map(|line| line.split(',').next()).collect();
+        assert_eq!(strategies, vec!["MACD", "MACD", "MACD", "HODL", "HODL"]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use std::f64::consts::PI;
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// Tiny seedable generator, good enough for synthetic data and noise while keeping runs reproducible
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn at_position(seed: u64, position: u64) -> Self {
+        Self { state: seed.wrapping_add(position.wrapping_mul(GOLDEN_GAMMA)) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn next_gaussian(&mut self) -> f64 {
+        let u1 = 1. - self.next_f64();
+        let u2 = self.next_f64();
+        (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+    }
+}
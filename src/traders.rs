@@ -1,17 +1,62 @@
 mod generic_trader;
-pub use generic_trader::{GenericTrader, StakeSize, TradingFee};
+pub use generic_trader::{DirectionMode, FeeApplication, GenericTrader, Liquidity, PositionPolicy, StakeSize, StopOutClock, StrengthScale, TraderSettings, TradingFee, TransitionLimit};
+
+mod slippage;
+pub use slippage::{Slippage, Spread};
 
 mod macd_trader;
-pub use macd_trader::MACDTrader;
+pub use macd_trader::{MACDTrader, MacdConfig};
 
 mod hodl_trader;
 pub use hodl_trader::HODLTrader;
 
+mod cash_trader;
+pub use cash_trader::CashTrader;
+
 mod dca_trader;
-pub use dca_trader::DCATrader;
+pub use dca_trader::{DCATrader, DcaSizing};
 
 mod sma_trader;
 pub use sma_trader::SMATrader;
 
 mod sma2_trader;
 pub use sma2_trader::SMA2Trader;
+
+mod ema_trader;
+pub use ema_trader::EMATrader;
+
+mod vortex_trader;
+pub use vortex_trader::VortexTrader;
+
+mod rebalance_trader;
+pub use rebalance_trader::{RebalanceConfig, RebalanceTrader};
+
+mod ultimate_trader;
+pub use ultimate_trader::UltimateTrader;
+
+mod linreg_trader;
+pub use linreg_trader::LinRegTrader;
+
+mod ppo_trader;
+pub use ppo_trader::PPOTrader;
+
+mod squeeze_trader;
+pub use squeeze_trader::SqueezeTrader;
+
+mod wma_trader;
+pub use wma_trader::WMATrader;
+
+mod random_trader;
+pub use random_trader::RandomTrader;
+
+mod twap_trader;
+pub use twap_trader::{TwapConfig, TwapTrader};
+
+mod target_multiple_trader;
+pub use target_multiple_trader::{TargetConfig, TargetMultipleTrader};
+
+mod ribbon_trader;
+pub use ribbon_trader::RibbonTrader;
+
+mod rsi_trader;
+pub use rsi_trader::RSITrader;
@@ -0,0 +1,76 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::Cash;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+// Never trades, its flat equity curve is the "do nothing" baseline
+pub struct CashTrader {
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+}
+
+impl CashTrader {
+    pub fn new(kline_feed: &[BinanceKline]) -> Result<Self> {
+        debug!("Creating a Cash Trader");
+        let cash = Cash;
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in Cash feed")))?;
+        let cash = cash.init(next_kline)?;
+        Ok(Self { indicator: Box::new(cash), settings: TraderSettings::default() })
+    }
+}
+
+impl GenericTrader for CashTrader {
+    fn stake_size(&self) -> StakeSize {
+        StakeSize::FixAmount(0.0)
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        TradingFee::FixFee(0.0)
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, _signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with cash signal");
+        Ok(Action::None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, Position};
+    use crate::data::generate_klines;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_cash_equity_stays_at_starting_capital() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(200, start_time, Duration::hours(1), 5);
+        let mut trader = CashTrader::new(&klines)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        for kline in &klines {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+
+        assert!(account.trade_history.is_empty());
+        assert_eq!(account.profit_and_loss_history.len(), klines.len() + 1);
+        assert!(account.profit_and_loss_history.iter().all(|time_value| time_value.equity == 1000.0));
+
+        Ok(())
+    }
+}
@@ -1,45 +1,105 @@
 use crate::data::BinanceKline;
+use crate::error::{Error, Result};
 use crate::indicators::BinanceIndicatorInstance;
 use crate::indicators::Dca;
-use crate::traders::{GenericTrader, StakeSize, TradingFee};
-use anyhow::{anyhow, Result};
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
 use yata::core::Action;
 use yata::prelude::*;
 
 use log::debug;
 
+/// How much each periodic DCA buy stakes
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DcaSizing {
+    /// Every period buys the configured stake size
+    #[default]
+    FixedSlice,
+    /// Every period buys this fraction of the cash left, front-loading the accumulation
+    PercentOfCash(f64),
+}
+
 pub struct DCATrader {
     trading_fee: TradingFee,
     stake_size: StakeSize,
+    sizing: DcaSizing,
+    settings: TraderSettings,
     indicator: Box<dyn BinanceIndicatorInstance>,
 }
 
 impl DCATrader {
-    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee) -> Result<Self> {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize) -> Result<Self> {
         debug!("Creating a DCA Trader");
         let dca = Dca;
-        let next_kline = kline_feed.first().ok_or(anyhow!("No klines in DCA feed"))?;
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in DCA feed")))?;
         let dca = dca.init(next_kline)?;
-        Ok(Self { indicator: Box::new(dca), trading_fee, stake_size: StakeSize::FixAmount(100.0) })
+        Ok(Self { indicator: Box::new(dca), trading_fee, stake_size, sizing: DcaSizing::default(), settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_sizing(mut self, sizing: DcaSizing) -> Self {
+        self.sizing = sizing;
+        self
     }
 }
 
 impl GenericTrader for DCATrader {
     fn stake_size(&self) -> StakeSize {
-        self.stake_size
+        match self.sizing {
+            DcaSizing::FixedSlice => self.stake_size,
+            DcaSizing::PercentOfCash(pct) => StakeSize::FixPercentage(pct),
+        }
     }
 
     fn trading_fee(&self) -> TradingFee {
-        self.trading_fee
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
     }
 
     fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
         self.indicator.as_mut()
     }
 
-    fn determine_trade(signals: &[Action]) -> Result<Action> {
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
         debug!("Determine trades with dca signal");
-        let val = signals.first().ok_or(anyhow!("No dca signal found"))?;
-        Ok(*val)
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, Position};
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    fn period_buys(sizing: DcaSizing) -> Result<Vec<f64>> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 2, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0; 4], start_time, Duration::days(31));
+        let mut trader = DCATrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixAmount(100.0))?.with_sizing(sizing);
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        for kline in &klines {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+
+        Ok(account.trade_history.iter().map(|trade| trade.quantity * trade.price).collect())
+    }
+
+    #[test]
+    fn test_percent_of_cash_buys_shrink_while_fixed_slices_stay_constant() -> Result<()> {
+        assert_eq!(period_buys(DcaSizing::FixedSlice)?, vec![100.0, 100.0, 100.0, 100.0]);
+        assert_eq!(period_buys(DcaSizing::PercentOfCash(0.5))?, vec![500.0, 250.0, 125.0, 62.5]);
+
+        Ok(())
     }
 }
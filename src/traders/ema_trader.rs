@@ -0,0 +1,60 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::EmaPair;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+pub struct EMATrader {
+    trading_fee: TradingFee,
+    stake_size: StakeSize,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+}
+
+impl EMATrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, short_window: u8, long_window: u8) -> Result<Self> {
+        debug!("Creating an EMA Trader");
+        if short_window == 0 || short_window >= long_window {
+            return Err(Error::InvalidConfig(format!("EMA short window {short_window} must be positive and shorter than long window {long_window}")));
+        }
+
+        let ema_pair = EmaPair::new(short_window, long_window);
+
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in EMA feed")))?;
+        let ema = ema_pair.init(next_kline)?;
+        Ok(Self { indicator: Box::new(ema), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl GenericTrader for EMATrader {
+    fn stake_size(&self) -> StakeSize {
+        self.stake_size
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with EMA signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+}
@@ -1,81 +1,1064 @@
-use crate::account::Account;
-use crate::data::BinanceKline;
-use crate::indicators::BinanceIndicatorInstance;
-use anyhow::Result;
-use chrono::NaiveDateTime;
-use log::debug;
+use crate::account::{Account, BuySellIndicator};
+use crate::data::{BinanceKline, SymbolFilters, TimestampConvention};
+use crate::error::{Error, Result};
+use crate::indicators::{BinanceIndicatorInstance, ChandelierInstance};
+use crate::traders::{Slippage, Spread};
+use chrono::{Duration, NaiveDateTime};
+use log::{debug, warn};
 use yata::core::Action;
 
 #[allow(dead_code)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TradingFee {
+    /// Flat amount charged on every open and close regardless of order size
     FixFee(f64),
     PercentageFee(f64),
+    /// `(threshold, rate)` tiers sorted by threshold, the rate of the highest threshold reached by the
+    /// 30-day traded notional applies and volume below the first threshold pays the first rate
+    VolumeTiered(Vec<(f64, f64)>),
+    /// Percentage rates for orders resting on the book and for orders crossing the spread
+    MakerTaker {
+        maker: f64,
+        taker: f64,
+    },
 }
 
-#[derive(Clone, Copy)]
+impl TradingFee {
+    pub fn compute(&self, notional: f64, recent_volume: f64, liquidity: Liquidity) -> f64 {
+        match self {
+            TradingFee::FixFee(fee) => *fee,
+            TradingFee::PercentageFee(pct) => notional * pct,
+            TradingFee::MakerTaker { maker, taker } => match liquidity {
+                Liquidity::Maker => notional * maker,
+                Liquidity::Taker => notional * taker,
+            },
+            TradingFee::VolumeTiered(tiers) => {
+                let rate = tiers.iter().take_while(|(threshold, _)| *threshold <= recent_volume).last().or(tiers.first()).map_or(0., |(_, rate)| *rate);
+                notional * rate
+            }
+        }
+    }
+}
+
+/// Whether an order added liquidity to the book or removed it, only `TradingFee::MakerTaker` prices them differently
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Liquidity {
+    /// Rested on the book until another order filled it
+    Maker,
+    /// Crossed the spread and filled immediately, which every market fill of the backtest does
+    #[default]
+    Taker,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum StakeSize {
     FixAmount(f64),
     FixPercentage(f64),
+    /// Multiplies `base` by `multiplier` after every losing round trip, up to `max_steps` times.
+    /// Risky: a long losing streak quickly escalates exposure, `max_steps` is the only brake.
+    Martingale {
+        base: f64,
+        multiplier: f64,
+        max_steps: usize,
+    },
+    /// Multiplies `base` by `multiplier` after every winning round trip, up to `max_steps` times.
+    /// Risky: the whole escalated stake is given back on the first loss after a winning streak.
+    AntiMartingale {
+        base: f64,
+        multiplier: f64,
+        max_steps: usize,
+    },
+    /// Stakes the Kelly fraction `win_rate - (1 - win_rate) / win_loss_ratio` of the available fund, clamped to [0, 1].
+    /// A negative fraction means the edge is against the trader and no trade is taken.
+    Kelly {
+        win_rate: f64,
+        win_loss_ratio: f64,
+    },
+}
+
+impl StakeSize {
+    pub fn stake(&self, account: &Account) -> f64 {
+        let fund = account.available_fund;
+        let amount = match *self {
+            StakeSize::FixAmount(amount) => amount,
+            StakeSize::FixPercentage(pct) => return fund * pct,
+            StakeSize::Kelly { win_rate, win_loss_ratio } => {
+                let fraction = if win_loss_ratio > 0. { win_rate - (1. - win_rate) / win_loss_ratio } else { 0. };
+                return fund * fraction.clamp(0., 1.);
+            }
+            StakeSize::Martingale { base, multiplier, max_steps } => {
+                let losses = account.round_trip_pnls().iter().rev().take_while(|pnl| **pnl < 0.).count();
+                base * multiplier.powi(losses.min(max_steps) as i32)
+            }
+            StakeSize::AntiMartingale { base, multiplier, max_steps } => {
+                let wins = account.round_trip_pnls().iter().rev().take_while(|pnl| **pnl > 0.).count();
+                base * multiplier.powi(wins.min(max_steps) as i32)
+            }
+        };
+        if amount <= fund {
+            amount
+        } else {
+            0.
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FeeApplication {
+    /// The stake plus the fee buys the position, the original sizing and still the default
+    #[default]
+    GrossedUp,
+    /// The fee is charged on top of the stake, the whole stake buys the position
+    Additive,
+    /// The fee is taken out of the stake, only the remainder buys the position
+    Inclusive,
+}
+
+/// How the strength `n` of `Action::Buy(n)` / `Action::Sell(n)` sizes a trade.
+/// Buys stake that fraction of the resolved stake, sells close that fraction of the position.
+/// Indicators with a continuous confidence publish it as a value instead and are read with `Conviction`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StrengthScale {
+    /// Always trade in full, the built-in indicators all emit strength 1
+    #[default]
+    Ignore,
+    /// Strength `n` out of `max` trades `min(n, max) / max`
+    OutOf(u8),
+    /// The indicator value at this index is a conviction in [0, 1] that is traded as is, 0.5 stakes half.
+    /// The integer strength is ignored, values outside the range are clamped and a missing value trades in full.
+    Conviction(usize),
+}
+
+impl StrengthScale {
+    pub fn fraction(&self, strength: u8, values: &[f64]) -> f64 {
+        match *self {
+            StrengthScale::OutOf(max) if max > 0 => strength.min(max) as f64 / max as f64,
+            StrengthScale::Conviction(index) => values.get(index).copied().filter(|conviction| conviction.is_finite()).map_or(1., |conviction| conviction.clamp(0., 1.)),
+            _ => 1.,
+        }
+    }
+}
+
+/// How many account state transitions a single candle may cause.
+/// Exits outrank fresh signals: a chandelier stop first, then an equity guard hit at the close.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TransitionLimit {
+    /// Act on every exit and signal, e.g. buy on the signal and flatten on the equity guard in the same candle
+    #[default]
+    Unlimited,
+    /// Execute only the highest priority action and log the ones it overrides
+    OnePerCandle,
+}
+
+/// How a held position reacts to `Action::None` and to repeated same-direction signals.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PositionPolicy {
+    /// Act on every signal and hold through `None`, repeated buys add to the position as DCA relies on
+    #[default]
+    EverySignal,
+    /// Close the whole position as soon as the signal goes quiet
+    FlatOnNone,
+    /// Stay in the position until the opposite signal, ignoring repeats and `None`
+    HoldUntilOpposite,
+}
+
+/// Which side of the market the trader may hold.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DirectionMode {
+    /// Buys open longs and sells only go back to cash
+    #[default]
+    LongOnly,
+    /// A sell closes any long and goes short, a buy covers and goes long, sized by signal strength and subject to the position policy
+    LongShort,
+}
+
+/// Candles since the trailing stop last closed a position, `TraderSettings::cooldown` is measured against it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StopOutClock {
+    candles_since: Option<usize>,
+}
+
+impl StopOutClock {
+    pub fn candles_since(&self) -> Option<usize> {
+        self.candles_since
+    }
+
+    fn tick(&mut self) {
+        if let Some(candles) = self.candles_since.as_mut() {
+            *candles += 1;
+        }
+    }
+
+    fn stop_out(&mut self) {
+        self.candles_since = Some(0);
+    }
+
+    fn cooling_down(&self, cooldown: usize) -> bool {
+        self.candles_since.is_some_and(|candles| candles <= cooldown)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TraderSettings {
+    pub fee_application: FeeApplication,
+    pub slippage: Slippage,
+    /// Half of it is paid on every fill on top of slippage, buys at the ask and sells at the bid
+    pub spread: Spread,
+    /// Share of available cash never staked, kept back to cover fees and slippage
+    pub reserve_pct: f64,
+    /// Exchange lot size, tick size and minimum notional applied to every fill
+    pub symbol_filters: Option<SymbolFilters>,
+    pub strength_scale: StrengthScale,
+    /// Candle end used for signal gating, trade timestamps and equity marks
+    pub timestamp_convention: TimestampConvention,
+    pub transition_limit: TransitionLimit,
+    pub position_policy: PositionPolicy,
+    pub direction_mode: DirectionMode,
+    /// Candles after a trailing stop exit during which new entries are ignored, only valid for traders with a trailing stop
+    pub cooldown: usize,
+    /// Time a position must be held before a signal may close it, stops and equity guards still apply
+    pub min_hold: Option<Duration>,
 }
 
 pub trait GenericTrader {
     fn stake_size(&self) -> StakeSize;
     fn trading_fee(&self) -> TradingFee;
+    fn settings(&self) -> &TraderSettings;
     fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance;
-    fn determine_trade(signals: &[Action]) -> Result<Action>;
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action>;
 
-    fn execute_buy(&self, timestamp: NaiveDateTime, price: f64, account: &mut Account) {
-        let fund = account.available_fund;
-        let stake = match self.stake_size() {
-            StakeSize::FixAmount(amount) => {
-                if amount <= fund {
-                    amount
-                } else {
-                    0.
-                }
+    fn warm_up_period(&mut self) -> usize {
+        self.indicator().warm_up_period()
+    }
+
+    /// Trailing stop checked before the trader's own signal for longs and shorts, traders opt in by returning their instance
+    fn trailing_stop(&mut self) -> Option<&mut ChandelierInstance> {
+        None
+    }
+
+    /// State behind `TraderSettings::cooldown`, traders with a trailing stop return theirs to enable it
+    fn stop_out_clock(&mut self) -> Option<&mut StopOutClock> {
+        None
+    }
+
+    /// Liquidity an order on `side` is charged as, a limit order model would return `Liquidity::Maker` for resting orders
+    fn liquidity(&self, _side: BuySellIndicator) -> Liquidity {
+        Liquidity::Taker
+    }
+
+    fn fill_price(&self, intended_price: f64, side: BuySellIndicator, account: &Account) -> f64 {
+        let mid = self.settings().slippage.fill_price(intended_price, account.trade_history.len());
+        let price = self.settings().spread.fill_price(mid, side);
+        match self.settings().symbol_filters {
+            Some(filters) => filters.round_price(price),
+            None => price,
+        }
+    }
+
+    // Stake, quantity and fee of an order for `fraction` of the resolved stake, None when the filters reject it
+    fn stake_quantity(&self, timestamp: NaiveDateTime, price: f64, fraction: f64, side: BuySellIndicator, account: &Account) -> Option<(f64, f64, f64)> {
+        let stake = self.stake_size().stake(account).min(account.available_fund * (1. - self.settings().reserve_pct)).max(0.) * fraction;
+        let fee = self.trading_fee().compute(stake, account.recent_volume(timestamp), self.liquidity(side));
+        let quantity = match self.settings().fee_application {
+            FeeApplication::GrossedUp => (stake + fee) / price,
+            FeeApplication::Additive => stake / price,
+            FeeApplication::Inclusive => (stake - fee) / price,
+        };
+        let quantity = match self.settings().symbol_filters {
+            Some(filters) if !filters.meets_min_notional(filters.round_quantity(quantity), price) => {
+                debug!("{timestamp}, order below minimum notional of {}", filters.min_notional);
+                return None;
             }
-            StakeSize::FixPercentage(pct) => fund * pct,
+            Some(filters) => filters.round_quantity(quantity),
+            None => quantity,
         };
-        let fee = match self.trading_fee() {
-            TradingFee::FixFee(fee) => fee,
-            TradingFee::PercentageFee(pct) => stake * pct / (1.0 - pct),
+        Some((stake, quantity, fee))
+    }
+
+    fn execute_buy(&self, timestamp: NaiveDateTime, intended_price: f64, fraction: f64, account: &mut Account) -> Result<()> {
+        let price = self.fill_price(intended_price, BuySellIndicator::Buy, account);
+        let fund = account.available_fund;
+        let Some((stake, quantity, fee)) = self.stake_quantity(timestamp, price, fraction, BuySellIndicator::Buy, account) else {
+            return Ok(());
         };
-        let quantity = (stake + fee) / price;
 
         if quantity > 0. {
             debug!("{}, B {:.08} @ ${:.08}, available_fund: {:.02}", timestamp, quantity, price, fund - stake);
-            account.open(timestamp, quantity, price, fee);
+            account.open_at_fill(timestamp, quantity, intended_price, price, fee)?;
         }
+
+        Ok(())
     }
 
-    fn execute_sell(&self, timestamp: NaiveDateTime, price: f64, account: &mut Account) -> Result<()> {
-        let current_position = account.position.quantity;
-        let fee = match self.trading_fee() {
-            TradingFee::FixFee(fee) => fee,
-            TradingFee::PercentageFee(pct) => price * current_position * pct,
+    fn execute_short(&self, timestamp: NaiveDateTime, intended_price: f64, fraction: f64, account: &mut Account) -> Result<()> {
+        let price = self.fill_price(intended_price, BuySellIndicator::Sell, account);
+        let Some((_, quantity, fee)) = self.stake_quantity(timestamp, price, fraction, BuySellIndicator::Sell, account) else {
+            return Ok(());
         };
-        if current_position > 0. {
-            debug!("{timestamp}, S {current_position:.08} @ $ {price:0.8}");
-            account.close(timestamp, current_position, price, fee)?
+
+        if quantity > 0. {
+            debug!("{timestamp}, SS {quantity:.08} @ ${price:.08}");
+            account.open_short_at_fill(timestamp, quantity, intended_price, price, fee)?;
+        }
+
+        Ok(())
+    }
+
+    fn execute_cover(&self, timestamp: NaiveDateTime, intended_price: f64, account: &mut Account) -> Result<()> {
+        let price = self.fill_price(intended_price, BuySellIndicator::Buy, account);
+        let quantity = -account.position.quantity;
+        let fee = self.trading_fee().compute(price * quantity, account.recent_volume(timestamp), self.liquidity(BuySellIndicator::Buy));
+        if quantity > 0. {
+            debug!("{timestamp}, BC {quantity:.08} @ ${price:.08}");
+            account.close_short_at_fill(timestamp, quantity, intended_price, price, fee)?;
+        }
+
+        Ok(())
+    }
+
+    // Reverses into the signal's direction with `fraction` of the stake, a repeated signal keeps the side already held
+    // under every policy and `FlatOnNone` closes either side once the signal goes quiet
+    fn flip_position(&self, timestamp: NaiveDateTime, price: f64, trade: Action, fraction: f64, account: &mut Account) -> Result<()> {
+        let quantity = account.position.quantity;
+        match trade {
+            Action::Buy(_) if quantity <= 0. => {
+                self.execute_cover(timestamp, price, account)?;
+                self.execute_buy(timestamp, price, fraction, account)
+            }
+            Action::Sell(_) if quantity >= 0. => {
+                self.execute_sell(timestamp, price, 1., account)?;
+                self.execute_short(timestamp, price, fraction, account)
+            }
+            Action::None if self.settings().position_policy == PositionPolicy::FlatOnNone && quantity != 0. => {
+                debug!("{timestamp}, signal went quiet, flattening");
+                self.execute_sell(timestamp, price, 1., account)?;
+                self.execute_cover(timestamp, price, account)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn execute_sell(&self, timestamp: NaiveDateTime, intended_price: f64, fraction: f64, account: &mut Account) -> Result<()> {
+        let price = self.fill_price(intended_price, BuySellIndicator::Sell, account);
+        let quantity = account.position.quantity * fraction;
+        let fee = self.trading_fee().compute(price * quantity, account.recent_volume(timestamp), self.liquidity(BuySellIndicator::Sell));
+        if quantity > 0. {
+            debug!("{timestamp}, S {quantity:.08} @ $ {price:0.8}");
+            account.close_at_fill(timestamp, quantity, intended_price, price, fee)?
         }
 
         Ok(())
     }
 
     fn next_trade_session(&mut self, account: &mut Account, kline: &BinanceKline) -> Result<()> {
-        let timestamp = kline.end_time;
+        if account.is_halted() {
+            debug!("Trading halted");
+            return Ok(());
+        }
+
+        if self.settings().cooldown > 0 && (self.trailing_stop().is_none() || self.stop_out_clock().is_none()) {
+            return Err(Error::InvalidConfig(String::from("Cooldown needs a trader with a trailing stop and its stop-out clock")));
+        }
+
+        let timestamp_convention = self.settings().timestamp_convention;
+        let timestamp = kline.timestamp(timestamp_convention);
         let price = kline.close;
+        if let Some(clock) = self.stop_out_clock() {
+            clock.tick();
+        }
+
+        let (long_stopped_out, short_stopped_out) = match self.trailing_stop() {
+            Some(stop) => {
+                let exits = stop.next_binance_kline(kline);
+                (exits.signals().first() == Some(&Action::Sell(1)), exits.signals().get(1) == Some(&Action::Buy(1)))
+            }
+            None => (false, false),
+        };
 
+        self.indicator().set_timestamp_convention(timestamp_convention);
         let indicator = self.indicator().next_binance_kline(kline);
         let signals = indicator.signals();
-        let trade = Self::determine_trade(signals)?;
+        let trade = self.determine_trade(signals)?;
+        let quantity = account.position.quantity;
+        if (long_stopped_out && quantity > 0.) || (short_stopped_out && quantity < 0.) {
+            debug!("{timestamp}, chandelier exit hit at ${price:.08}");
+            if trade != Action::None {
+                warn!("{timestamp}, chandelier exit overrides {trade:?}");
+            }
+            if let Some(clock) = self.stop_out_clock() {
+                clock.stop_out();
+            }
+            return if quantity > 0. { self.execute_sell(timestamp, price, 1., account) } else { self.execute_cover(timestamp, price, account) };
+        }
+        if trade != Action::None && self.settings().transition_limit == TransitionLimit::OnePerCandle && account.equity_guard_breached_at(price) {
+            warn!("{timestamp}, equity guard exit overrides {trade:?}");
+            return Ok(());
+        }
+        let direction_mode = self.settings().direction_mode;
+        let entry = matches!(trade, Action::Buy(_)) || (matches!(trade, Action::Sell(_)) && direction_mode == DirectionMode::LongShort);
+        let cooldown = self.settings().cooldown;
+        if entry && self.stop_out_clock().is_some_and(|clock| clock.cooling_down(cooldown)) {
+            debug!("{timestamp}, cooling down after a stop-out, ignoring {trade:?}");
+            return Ok(());
+        }
+        let position_policy = self.settings().position_policy;
+        let exit = match trade {
+            Action::Sell(_) => quantity > 0.,
+            Action::Buy(_) => quantity < 0.,
+            _ => position_policy == PositionPolicy::FlatOnNone && quantity != 0.,
+        };
+        let min_hold = self.settings().min_hold;
+        if exit && min_hold.is_some_and(|min_hold| account.entry_time.is_some_and(|entry_time| timestamp - entry_time < min_hold)) {
+            debug!("{timestamp}, position held less than the minimum, ignoring {trade:?}");
+            return Ok(());
+        }
+        let strength_scale = self.settings().strength_scale;
+        if direction_mode == DirectionMode::LongShort {
+            let fraction = match trade {
+                Action::Buy(strength) | Action::Sell(strength) => strength_scale.fraction(strength, indicator.values()),
+                _ => 1.,
+            };
+            return self.flip_position(timestamp, price, trade, fraction, account);
+        }
+        let holding = quantity > 0.;
+        match (position_policy, trade) {
+            (PositionPolicy::FlatOnNone, Action::None) if holding => {
+                debug!("{timestamp}, signal went quiet, flattening");
+                return self.execute_sell(timestamp, price, 1., account);
+            }
+            (PositionPolicy::HoldUntilOpposite, Action::Buy(_)) if holding => {
+                debug!("{timestamp}, already long, ignoring repeated buy");
+                return Ok(());
+            }
+            _ => {}
+        }
         match trade {
-            Action::Buy(_) => self.execute_buy(timestamp, price, account),
-            Action::Sell(_) => self.execute_sell(timestamp, price, account)?,
+            Action::Buy(strength) => self.execute_buy(timestamp, price, strength_scale.fraction(strength, indicator.values()), account)?,
+            Action::Sell(strength) => self.execute_sell(timestamp, price, strength_scale.fraction(strength, indicator.values()), account)?,
             _ => debug!("Nothing to do"),
         };
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{BuySellIndicator, Position, ProfitHandling};
+    use crate::data::klines_from_closes;
+    use crate::error::Error;
+    use crate::indicators::ChandelierConfig;
+    use chrono::{Duration, NaiveDate};
+    use yata::core::{IndicatorConfig, IndicatorResult};
+
+    fn create_timestamp(year: i32, month: u32, day: u32) -> Result<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(Error::Parse(String::from("Cannot create timestamp")))
+    }
+
+    struct ScriptedIndicator {
+        actions: Vec<Action>,
+        values: Vec<f64>,
+        position: usize,
+    }
+
+    impl BinanceIndicatorInstance for ScriptedIndicator {
+        fn next_binance_kline(&mut self, _candle: &BinanceKline) -> IndicatorResult {
+            let action = self.actions.get(self.position).copied().unwrap_or(Action::None);
+            self.position += 1;
+            IndicatorResult::new(&self.values, &[action])
+        }
+    }
+
+    struct ScriptedTrader {
+        stake_size: StakeSize,
+        trading_fee: TradingFee,
+        settings: TraderSettings,
+        indicator: ScriptedIndicator,
+        trailing_stop: Option<ChandelierInstance>,
+        stop_out_clock: StopOutClock,
+    }
+
+    impl ScriptedTrader {
+        fn new(actions: Vec<Action>, stake_size: StakeSize, trading_fee: TradingFee, settings: TraderSettings) -> Self {
+            Self { stake_size, trading_fee, settings, indicator: ScriptedIndicator { actions, values: Vec::new(), position: 0 }, trailing_stop: None, stop_out_clock: StopOutClock::default() }
+        }
+    }
+
+    impl GenericTrader for ScriptedTrader {
+        fn stake_size(&self) -> StakeSize {
+            self.stake_size
+        }
+
+        fn trading_fee(&self) -> TradingFee {
+            self.trading_fee.clone()
+        }
+
+        fn settings(&self) -> &TraderSettings {
+            &self.settings
+        }
+
+        fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+            &mut self.indicator
+        }
+
+        fn trailing_stop(&mut self) -> Option<&mut ChandelierInstance> {
+            self.trailing_stop.as_mut()
+        }
+
+        fn stop_out_clock(&mut self) -> Option<&mut StopOutClock> {
+            Some(&mut self.stop_out_clock)
+        }
+
+        fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+            let val = signals.first().ok_or(Error::NoData(String::from("No scripted signal found")))?;
+            Ok(*val)
+        }
+    }
+
+    fn new_account() -> Result<Account> {
+        Ok(Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, create_timestamp(2024, 1, 1)?))
+    }
+
+    fn run_scripted(trader: &mut ScriptedTrader, mut account: Account, closes: &[f64]) -> Result<Account> {
+        let start_time = create_timestamp(2024, 1, 1)?;
+        for kline in klines_from_closes(closes, start_time, Duration::hours(1)) {
+            trader.next_trade_session(&mut account, &kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+        Ok(account)
+    }
+
+    #[test]
+    fn test_fee_application_changes_quantity() -> Result<()> {
+        let stake_size = StakeSize::FixAmount(100.0);
+        let trading_fee = TradingFee::PercentageFee(0.01);
+
+        let additive = TraderSettings { fee_application: FeeApplication::Additive, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], stake_size, trading_fee.clone(), additive);
+        let additive_account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+
+        let inclusive = TraderSettings { fee_application: FeeApplication::Inclusive, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], stake_size, trading_fee.clone(), inclusive);
+        let inclusive_account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], stake_size, trading_fee, TraderSettings::default());
+        let grossed_up_account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+
+        assert_eq!(additive_account.position.quantity, 1.0);
+        assert_eq!(additive_account.available_fund, 899.0);
+        assert_eq!(inclusive_account.position.quantity, 0.99);
+        assert_eq!(inclusive_account.available_fund, 900.0);
+        assert_eq!(grossed_up_account.position.quantity, 1.01);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fix_fee_is_flat_per_trade() -> Result<()> {
+        let round_trip_fees = |stake: f64| -> Result<Vec<f64>> {
+            let mut trader = ScriptedTrader::new(vec![Action::Buy(1), Action::Sell(1)], StakeSize::FixAmount(stake), TradingFee::FixFee(2.5), TraderSettings::default());
+            let account = run_scripted(&mut trader, new_account()?, &[5.0, 5.0])?;
+            Ok(account.trade_history.iter().map(|trade| trade.fee).collect())
+        };
+
+        // 100 units and 1 unit at $5 pay the same fee on both legs
+        assert_eq!(round_trip_fees(500.0)?, vec![2.5, 2.5]);
+        assert_eq!(round_trip_fees(5.0)?, vec![2.5, 2.5]);
+
+        Ok(())
+    }
+
+    fn round_trip(account: &mut Account, exit_price: f64) -> Result<()> {
+        let timestamp = create_timestamp(2024, 1, 1)?;
+        account.open(timestamp, 1.0, 100.0, 0.0)?;
+        account.close(timestamp, 1.0, exit_price, 0.0)
+    }
+
+    #[test]
+    fn test_martingale_escalates_on_losses_then_caps() -> Result<()> {
+        let stake_size = StakeSize::Martingale { base: 10.0, multiplier: 2.0, max_steps: 2 };
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, create_timestamp(2024, 1, 1)?);
+        assert_eq!(stake_size.stake(&account), 10.0);
+
+        round_trip(&mut account, 90.0)?;
+        assert_eq!(stake_size.stake(&account), 20.0);
+        round_trip(&mut account, 90.0)?;
+        assert_eq!(stake_size.stake(&account), 40.0);
+        round_trip(&mut account, 90.0)?;
+        assert_eq!(stake_size.stake(&account), 40.0);
+
+        round_trip(&mut account, 110.0)?;
+        assert_eq!(stake_size.stake(&account), 10.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_kelly_stakes_clamped_fraction_of_fund() -> Result<()> {
+        let account = new_account()?;
+        assert!((StakeSize::Kelly { win_rate: 0.6, win_loss_ratio: 2.0 }.stake(&account) - 400.0).abs() < 1e-9);
+        assert_eq!(StakeSize::Kelly { win_rate: 1.0, win_loss_ratio: 1.5 }.stake(&account), 1000.0);
+        assert_eq!(StakeSize::Kelly { win_rate: 0.6, win_loss_ratio: 0.0 }.stake(&account), 0.0);
+
+        // A negative edge sizes the entry at nothing, so the buy signal is skipped
+        let stake_size = StakeSize::Kelly { win_rate: 0.3, win_loss_ratio: 1.0 };
+        assert_eq!(stake_size.stake(&account), 0.0);
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], stake_size, TradingFee::PercentageFee(0.0), TraderSettings::default());
+        let account = run_scripted(&mut trader, account, &[100.0])?;
+        assert!(account.trade_history.is_empty());
+        assert_eq!(account.available_fund, 1000.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_anti_martingale_escalates_on_wins() -> Result<()> {
+        let stake_size = StakeSize::AntiMartingale { base: 10.0, multiplier: 2.0, max_steps: 3 };
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, create_timestamp(2024, 1, 1)?);
+
+        round_trip(&mut account, 110.0)?;
+        assert_eq!(stake_size.stake(&account), 20.0);
+        round_trip(&mut account, 110.0)?;
+        assert_eq!(stake_size.stake(&account), 40.0);
+
+        round_trip(&mut account, 90.0)?;
+        assert_eq!(stake_size.stake(&account), 10.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stop_loss_equity_flattens_and_halts_trading() -> Result<()> {
+        let actions = vec![Action::Buy(1), Action::None, Action::Buy(1), Action::Buy(1)];
+        let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), TraderSettings::default());
+        let account = new_account()?.with_equity_guards(None, Some(950.0));
+
+        let account = run_scripted(&mut trader, account, &[100.0, 80.0, 80.0, 90.0])?;
+
+        assert!(account.is_halted());
+        assert_eq!(account.position.quantity, 0.0);
+        assert_eq!(account.available_fund, 900.0);
+        assert_eq!(account.trade_history.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stochastic_fill_is_deterministic_per_seed() -> Result<()> {
+        let fill_prices = |seed: u64| -> Result<Vec<f64>> {
+            let settings = TraderSettings { slippage: Slippage::StochasticFill { bps_stddev: 10.0, seed }, ..Default::default() };
+            let mut trader = ScriptedTrader::new(vec![Action::Buy(1), Action::Sell(1), Action::Buy(1)], StakeSize::FixAmount(100.0), TradingFee::PercentageFee(0.0), settings);
+            let account = run_scripted(&mut trader, new_account()?, &[100.0, 100.0, 100.0])?;
+            Ok(account.trade_history.iter().map(|trade| trade.price).collect())
+        };
+
+        let first_run = fill_prices(7)?;
+        assert_eq!(first_run.len(), 3);
+        assert!(first_run.iter().all(|price| *price != 100.0));
+        assert_eq!(first_run, fill_prices(7)?);
+        assert_ne!(first_run, fill_prices(8)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reserve_leaves_cash_for_open_fee() -> Result<()> {
+        let trading_fee = TradingFee::PercentageFee(0.005);
+
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixPercentage(1.), trading_fee.clone(), TraderSettings::default());
+        let account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+        assert!(account.available_fund < 0.);
+
+        let settings = TraderSettings { fee_application: FeeApplication::Additive, reserve_pct: 0.005, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixPercentage(1.), trading_fee, settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+        assert!((account.position.quantity - 9.95).abs() < 1e-9);
+        assert!(account.available_fund >= 0.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_symbol_filters_round_fills() -> Result<()> {
+        let filters = SymbolFilters { step_size: 0.1, tick_size: 0.5, min_notional: 50.0 };
+        let settings = TraderSettings { symbol_filters: Some(filters), ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixAmount(100.0), TradingFee::PercentageFee(0.0), settings.clone());
+        let account = run_scripted(&mut trader, new_account()?, &[30.1])?;
+
+        assert_eq!(account.trade_history[0].price, 30.0);
+        assert!((account.position.quantity - 3.3).abs() < 1e-9);
+
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixAmount(40.0), TradingFee::PercentageFee(0.0), settings);
+        let account = run_scripted(&mut trader, new_account()?, &[30.1])?;
+        assert!(account.trade_history.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signal_strength_scales_notional() -> Result<()> {
+        let settings = TraderSettings { strength_scale: StrengthScale::OutOf(2), ..Default::default() };
+        let notional = |strength: u8| -> Result<f64> {
+            let mut trader = ScriptedTrader::new(vec![Action::Buy(strength)], StakeSize::FixAmount(100.0), TradingFee::PercentageFee(0.0), settings.clone());
+            let account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+            Ok(account.position.quantity * 100.0)
+        };
+
+        assert_eq!(notional(2)?, 100.0);
+        assert_eq!(notional(1)?, 50.0);
+        assert_eq!(notional(9)?, 100.0);
+        assert_eq!(StrengthScale::Ignore.fraction(1, &[]), 1.0);
+
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(2), Action::Sell(1)], StakeSize::FixAmount(100.0), TradingFee::PercentageFee(0.0), settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 100.0])?;
+        assert_eq!(account.position.quantity, 0.5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spread_round_trip_loses_spread_at_flat_price() -> Result<()> {
+        let settings = TraderSettings { spread: Spread::Bps(10.0), ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1), Action::Sell(1)], StakeSize::FixAmount(100.0), TradingFee::PercentageFee(0.0), settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 100.0])?;
+
+        let fills: Vec<f64> = account.trade_history.iter().map(|trade| trade.price).collect();
+        assert!((fills[0] - 100.05).abs() < 1e-9);
+        assert!((fills[1] - 99.95).abs() < 1e-9);
+        let loss = 1000.0 - account.available_fund;
+        assert!((loss - 100.0 * 0.1 / 100.05).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_conviction_value_scales_notional() -> Result<()> {
+        let settings = TraderSettings { strength_scale: StrengthScale::Conviction(0), ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixAmount(100.0), TradingFee::PercentageFee(0.0), settings);
+        trader.indicator.values = vec![0.5];
+        let account = run_scripted(&mut trader, new_account()?, &[100.0])?;
+
+        assert_eq!(account.position.quantity * 100.0, 50.0);
+        assert_eq!(StrengthScale::Conviction(0).fraction(1, &[1.5]), 1.0);
+        assert_eq!(StrengthScale::Conviction(1).fraction(1, &[0.5]), 1.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sweep_keeps_position_size_constant() -> Result<()> {
+        let actions = vec![Action::Buy(1), Action::Sell(1), Action::Buy(1), Action::Sell(1), Action::Buy(1)];
+        let closes = [100.0, 150.0, 100.0, 150.0, 100.0];
+        let buy_quantities = |profit_handling: ProfitHandling| -> Result<(Vec<f64>, Account)> {
+            let mut trader = ScriptedTrader::new(actions.clone(), StakeSize::FixPercentage(1.), TradingFee::PercentageFee(0.0), TraderSettings::default());
+            let account = run_scripted(&mut trader, new_account()?.with_profit_handling(profit_handling), &closes)?;
+            let quantities = account.trade_history.iter().filter(|trade| trade.buy_sell_indicator == BuySellIndicator::Buy).map(|trade| trade.quantity).collect();
+            Ok((quantities, account))
+        };
+
+        let (reinvested, _) = buy_quantities(ProfitHandling::Reinvest)?;
+        assert_eq!(reinvested, vec![10.0, 15.0, 22.5]);
+
+        let (swept, account) = buy_quantities(ProfitHandling::Sweep)?;
+        assert_eq!(swept, vec![10.0, 10.0, 10.0]);
+        assert_eq!(account.withdrawn, 1000.0);
+        assert_eq!(account.profit_and_loss_history.last().map(|time_value| time_value.equity), Some(2000.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chandelier_exit_stops_out_long() -> Result<()> {
+        let closes = [100.0, 105.0, 110.0, 108.0, 100.0];
+        let start_time = create_timestamp(2024, 1, 1)?;
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), TraderSettings::default());
+        let account = run_scripted(&mut trader, new_account()?, &closes[..4])?;
+        assert_eq!(account.position.quantity, 5.0);
+
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), TraderSettings::default());
+        trader.trailing_stop = Some(ChandelierConfig { period: 3, atr_mult: 1.0 }.init(&klines[0])?);
+        let account = run_scripted(&mut trader, new_account()?, &closes)?;
+
+        // Swing high 110 minus the 3 candle ATR of 5
+        let line = trader.trailing_stop.as_ref().and_then(ChandelierInstance::line);
+        assert_eq!(line, Some(105.0));
+        assert_eq!(account.position.quantity, 0.0);
+        assert_eq!(account.trade_history.len(), 2);
+        assert_eq!(account.trade_history[1].timestamp, klines[4].end_time);
+        assert_eq!(account.trade_history[1].price, 100.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_chandelier_exit_covers_short() -> Result<()> {
+        let closes = [100.0, 95.0, 90.0, 92.0, 100.0];
+        let klines = klines_from_closes(&closes, create_timestamp(2024, 1, 1)?, Duration::hours(1));
+        let settings = TraderSettings { direction_mode: DirectionMode::LongShort, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Sell(1)], StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+        trader.trailing_stop = Some(ChandelierConfig { period: 3, atr_mult: 1.0 }.init(&klines[0])?);
+        let account = run_scripted(&mut trader, new_account()?, &closes)?;
+
+        // Swing low 90 plus the 3 candle ATR of 5
+        let short_line = trader.trailing_stop.as_ref().and_then(ChandelierInstance::short_line);
+        assert_eq!(short_line, Some(95.0));
+        assert_eq!(account.position.quantity, 0.0);
+        assert_eq!(account.trade_history.len(), 2);
+        assert_eq!(account.trade_history[1].buy_sell_indicator, BuySellIndicator::Buy);
+        assert_eq!(account.trade_history[1].timestamp, klines[4].end_time);
+        assert_eq!(trader.stop_out_clock.candles_since(), Some(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cooldown_without_trailing_stop_is_rejected() -> Result<()> {
+        let settings = TraderSettings { cooldown: 2, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1)], StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+
+        let klines = klines_from_closes(&[100.0], create_timestamp(2024, 1, 1)?, Duration::hours(1));
+
+        assert!(matches!(trader.next_trade_session(&mut new_account()?, &klines[0]), Err(Error::InvalidConfig(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cooldown_suppresses_entries_after_stop_out() -> Result<()> {
+        let closes = [100.0, 105.0, 110.0, 108.0, 100.0, 101.0, 102.0, 103.0];
+        let klines = klines_from_closes(&closes, create_timestamp(2024, 1, 1)?, Duration::hours(1));
+        let actions = vec![Action::Buy(1), Action::None, Action::None, Action::None, Action::None, Action::Buy(1), Action::Buy(1), Action::Buy(1)];
+        let run = |cooldown: usize| -> Result<(Account, ScriptedTrader)> {
+            let settings = TraderSettings { cooldown, ..Default::default() };
+            let mut trader = ScriptedTrader::new(actions.clone(), StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+            trader.trailing_stop = Some(ChandelierConfig { period: 3, atr_mult: 1.0 }.init(&klines[0])?);
+            let account = run_scripted(&mut trader, new_account()?, &closes)?;
+            Ok((account, trader))
+        };
+
+        let (account, _) = run(0)?;
+        assert_eq!(account.trade_history[1].timestamp, klines[4].end_time);
+        assert_eq!(account.trade_history[2].timestamp, klines[5].end_time);
+
+        let (account, trader) = run(2)?;
+        assert_eq!(account.trade_history.len(), 3);
+        assert_eq!(account.trade_history[1].buy_sell_indicator, BuySellIndicator::Sell);
+        assert_eq!(account.trade_history[2].buy_sell_indicator, BuySellIndicator::Buy);
+        assert_eq!(account.trade_history[2].timestamp, klines[7].end_time);
+        assert_eq!(trader.stop_out_clock.candles_since(), Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_min_hold_defers_signal_exit() -> Result<()> {
+        let settings = TraderSettings { min_hold: Some(Duration::hours(2)), ..Default::default() };
+        let actions = vec![Action::Buy(1), Action::Sell(1), Action::Sell(1)];
+        let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 110.0, 120.0])?;
+
+        let klines = klines_from_closes(&[100.0, 110.0, 120.0], create_timestamp(2024, 1, 1)?, Duration::hours(1));
+        assert_eq!(account.trade_history.len(), 2);
+        assert_eq!(account.trade_history[1].timestamp, klines[2].end_time);
+        assert_eq!(account.trade_history[1].price, 120.0);
+        assert_eq!(account.entry_time, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_transition_per_candle_prefers_take_profit() -> Result<()> {
+        let actions = vec![Action::Buy(1), Action::Buy(1)];
+        let run = |transition_limit: TransitionLimit| -> Result<Account> {
+            let settings = TraderSettings { transition_limit, ..Default::default() };
+            let mut trader = ScriptedTrader::new(actions.clone(), StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+            run_scripted(&mut trader, new_account()?.with_equity_guards(Some(1050.0), None), &[100.0, 120.0])
+        };
+
+        let account = run(TransitionLimit::Unlimited)?;
+        assert_eq!(account.trade_history.len(), 3);
+        assert!(account.is_halted());
+
+        let account = run(TransitionLimit::OnePerCandle)?;
+        assert_eq!(account.trade_history.len(), 2);
+        assert_eq!(account.trade_history[1].buy_sell_indicator, BuySellIndicator::Sell);
+        assert_eq!(account.trade_history[1].quantity, 5.0);
+        assert_eq!(account.available_fund, 1100.0);
+        assert!(account.is_halted());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_policy_on_quiet_and_repeated_signals() -> Result<()> {
+        let run = |position_policy: PositionPolicy, actions: Vec<Action>| -> Result<Account> {
+            let settings = TraderSettings { position_policy, ..Default::default() };
+            let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+            run_scripted(&mut trader, new_account()?, &[100.0, 110.0, 120.0])
+        };
+
+        let quiet = vec![Action::Buy(1), Action::None, Action::Sell(1)];
+        let account = run(PositionPolicy::FlatOnNone, quiet.clone())?;
+        assert_eq!(account.trade_history.len(), 2);
+        assert_eq!(account.trade_history[1].price, 110.0);
+        assert_eq!(account.available_fund, 1050.0);
+
+        let account = run(PositionPolicy::HoldUntilOpposite, quiet)?;
+        assert_eq!(account.trade_history.len(), 2);
+        assert_eq!(account.trade_history[1].price, 120.0);
+        assert_eq!(account.available_fund, 1100.0);
+
+        let repeated = vec![Action::Buy(1), Action::Buy(1), Action::Sell(1)];
+        assert_eq!(run(PositionPolicy::EverySignal, repeated.clone())?.trade_history.len(), 3);
+        assert_eq!(run(PositionPolicy::HoldUntilOpposite, repeated)?.trade_history.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_short_flips_on_every_signal() -> Result<()> {
+        let settings = TraderSettings { direction_mode: DirectionMode::LongShort, ..Default::default() };
+        let actions = vec![Action::Buy(1), Action::Sell(1), Action::Sell(1), Action::Buy(1)];
+        let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+        let mut account = new_account()?;
+
+        let mut legs = Vec::new();
+        for kline in klines_from_closes(&[100.0, 125.0, 110.0, 100.0], create_timestamp(2024, 1, 1)?, Duration::hours(1)) {
+            trader.next_trade_session(&mut account, &kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+            legs.push((account.position.quantity, account.profit_and_loss_history.last().map(|time_value| time_value.realised_pnl)));
+        }
+
+        // Long 5 from 100 closed at 125, then short 4 from 125 covered at 100
+        assert_eq!(legs, vec![(5.0, Some(0.0)), (-4.0, Some(125.0)), (-4.0, Some(125.0)), (5.0, Some(225.0))]);
+        assert_eq!(account.available_fund, 725.0);
+        assert_eq!(account.trade_history.len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_short_applies_position_policy_and_strength() -> Result<()> {
+        let settings = TraderSettings { direction_mode: DirectionMode::LongShort, position_policy: PositionPolicy::FlatOnNone, ..Default::default() };
+        let actions = vec![Action::Buy(1), Action::None, Action::Sell(1), Action::None];
+        let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+        let mut account = new_account()?;
+
+        let mut positions = Vec::new();
+        for kline in klines_from_closes(&[100.0, 100.0, 100.0, 100.0], create_timestamp(2024, 1, 1)?, Duration::hours(1)) {
+            trader.next_trade_session(&mut account, &kline)?;
+            positions.push(account.position.quantity);
+        }
+        assert_eq!(positions, vec![5.0, 0.0, -5.0, 0.0]);
+
+        let settings = TraderSettings { direction_mode: DirectionMode::LongShort, strength_scale: StrengthScale::OutOf(2), ..Default::default() };
+        let actions = vec![Action::Buy(2), Action::Sell(1)];
+        let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 100.0])?;
+
+        // The long closes in full, the short is opened with half the stake
+        assert_eq!(account.position.quantity, -2.5);
+        assert_eq!(account.trade_history.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sell_from_flat_opens_short() -> Result<()> {
+        let settings = TraderSettings { direction_mode: DirectionMode::LongShort, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Sell(1)], StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 90.0])?;
+
+        assert_eq!(account.position.quantity, -5.0);
+        assert_eq!(account.trade_history.len(), 1);
+        assert_eq!(account.profit_and_loss_history.last().map(|time_value| time_value.unrealised_pnl), Some(50.0));
+
+        let mut long_only = ScriptedTrader::new(vec![Action::Sell(1)], StakeSize::FixAmount(500.0), TradingFee::PercentageFee(0.0), TraderSettings::default());
+        let account = run_scripted(&mut long_only, new_account()?, &[100.0])?;
+        assert_eq!(account.position.quantity, 0.0);
+        assert!(account.trade_history.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_volume_tier_lowers_fee_once_crossed() -> Result<()> {
+        let trading_fee = TradingFee::VolumeTiered(vec![(0.0, 0.01), (1000.0, 0.001)]);
+        let actions = vec![Action::Buy(1), Action::Sell(1), Action::Buy(1)];
+        let settings = TraderSettings { fee_application: FeeApplication::Additive, ..Default::default() };
+        let mut trader = ScriptedTrader::new(actions, StakeSize::FixAmount(500.0), trading_fee, settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 100.0, 100.0])?;
+
+        let fees: Vec<f64> = account.trade_history.iter().map(|trade| trade.fee).collect();
+        assert_eq!(fees.len(), 3);
+        assert!((fees[0] - 5.0).abs() < 1e-9);
+        assert!((fees[1] - 5.0).abs() < 1e-9);
+        assert!((fees[2] - 0.5).abs() < 1e-9);
+        assert_eq!(account.recent_volume(account.trade_history[2].timestamp + Duration::days(30)), 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_maker_taker_rates_per_liquidity() -> Result<()> {
+        let trading_fee = TradingFee::MakerTaker { maker: 0.001, taker: 0.002 };
+        assert!((trading_fee.compute(500.0, 0.0, Liquidity::Maker) - 0.5).abs() < 1e-9);
+        assert!((trading_fee.compute(500.0, 0.0, Liquidity::Taker) - 1.0).abs() < 1e-9);
+
+        // Market fills are charged the taker rate on both legs
+        let settings = TraderSettings { fee_application: FeeApplication::Additive, ..Default::default() };
+        let mut trader = ScriptedTrader::new(vec![Action::Buy(1), Action::Sell(1)], StakeSize::FixAmount(500.0), trading_fee, settings);
+        let account = run_scripted(&mut trader, new_account()?, &[100.0, 100.0])?;
+        let fees: Vec<f64> = account.trade_history.iter().map(|trade| trade.fee).collect();
+        assert_eq!(fees.len(), 2);
+        assert!(fees.iter().all(|fee| (fee - 1.0).abs() < 1e-9));
+        assert!((account.available_fund - 998.0).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_signals_mean_no_trade() -> Result<()> {
+        use crate::indicators::{Cadence, LinRegConfig, PpoConfig, RandomConfig, RibbonConfig, RsiConfig, SqueezeConfig, UltimateConfig, VortexConfig, WmaConfig};
+        use crate::traders::{
+            CashTrader, DCATrader, EMATrader, HODLTrader, LinRegTrader, MACDTrader, MacdConfig, PPOTrader, RSITrader, RandomTrader, RebalanceConfig, RebalanceTrader, RibbonTrader, SMA2Trader,
+            SMATrader, SqueezeTrader, TargetConfig, TargetMultipleTrader, TwapConfig, TwapTrader, UltimateTrader, VortexTrader, WMATrader,
+        };
+
+        let klines = klines_from_closes(&[100.0, 110.0, 120.0], create_timestamp(2024, 1, 1)?, Duration::hours(1));
+        let fee = || TradingFee::PercentageFee(0.0);
+        let stake = StakeSize::FixPercentage(1.);
+        let traders: Vec<Box<dyn GenericTrader>> = vec![
+            Box::new(MACDTrader::new(&klines, fee(), stake, MacdConfig::default())?),
+            Box::new(HODLTrader::new(&klines, fee(), stake)?),
+            Box::new(CashTrader::new(&klines)?),
+            Box::new(DCATrader::new(&klines, fee(), stake)?),
+            Box::new(SMATrader::new(&klines, fee(), stake, 1, 2)?),
+            Box::new(SMA2Trader::new(&klines, fee(), stake, 1, 2)?),
+            Box::new(EMATrader::new(&klines, fee(), stake, 1, 2)?),
+            Box::new(VortexTrader::new(&klines, fee(), stake, VortexConfig { period: 2 })?),
+            Box::new(UltimateTrader::new(&klines, fee(), stake, UltimateConfig::default())?),
+            Box::new(LinRegTrader::new(&klines, fee(), stake, LinRegConfig { period: 2, stddev_mult: 2.0 })?),
+            Box::new(PPOTrader::new(&klines, fee(), stake, PpoConfig::default())?),
+            Box::new(WMATrader::new(&klines, fee(), stake, WmaConfig::default())?),
+            Box::new(SqueezeTrader::new(&klines, fee(), stake, SqueezeConfig::default())?),
+            Box::new(RebalanceTrader::new(&klines, fee(), RebalanceConfig { target_weight: 0.5, cadence: 1 })?),
+            Box::new(TwapTrader::new(&klines, fee(), stake, TwapConfig { slices: 2, trigger: Box::new(Cadence::new(1).init(&klines[0])?) })?),
+            Box::new(RandomTrader::new(&klines, fee(), stake, RandomConfig { entry_prob: 0.5, seed: 1 })?),
+            Box::new(TargetMultipleTrader::new(&klines, fee(), stake, TargetConfig { multiple: 3.0 })?),
+            Box::new(RibbonTrader::new(&klines, fee(), stake, RibbonConfig::default())?),
+            Box::new(RSITrader::new(&klines, fee(), stake, RsiConfig::default())?),
+        ];
+
+        for trader in &traders {
+            assert_eq!(trader.determine_trade(&[])?, Action::None);
+        }
+        assert_eq!(traders[0].determine_trade(&[Action::Buy(1)])?, Action::None);
+        assert_eq!(traders[2].determine_trade(&[Action::Buy(1)])?, Action::None);
+
+        Ok(())
+    }
+}
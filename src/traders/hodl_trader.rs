@@ -1,8 +1,8 @@
 use crate::data::BinanceKline;
+use crate::error::{Error, Result};
 use crate::indicators::BinanceIndicatorInstance;
 use crate::indicators::Hodl;
-use crate::traders::{GenericTrader, StakeSize, TradingFee};
-use anyhow::{anyhow, Result};
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
 use yata::core::Action;
 use yata::prelude::*;
 
@@ -11,16 +11,22 @@ use log::debug;
 pub struct HODLTrader {
     trading_fee: TradingFee,
     stake_size: StakeSize,
+    settings: TraderSettings,
     indicator: Box<dyn BinanceIndicatorInstance>,
 }
 
 impl HODLTrader {
-    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee) -> Result<Self> {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize) -> Result<Self> {
         debug!("Creating a HODL Trader");
         let hodl = Hodl;
-        let next_kline = kline_feed.first().ok_or(anyhow!("No klines in HODL feed"))?;
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in HODL feed")))?;
         let hodl = hodl.init(next_kline)?;
-        Ok(Self { indicator: Box::new(hodl), trading_fee, stake_size: StakeSize::FixPercentage(1.) })
+        Ok(Self { indicator: Box::new(hodl), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
     }
 }
 
@@ -30,16 +36,20 @@ impl GenericTrader for HODLTrader {
     }
 
     fn trading_fee(&self) -> TradingFee {
-        self.trading_fee
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
     }
 
     fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
         self.indicator.as_mut()
     }
 
-    fn determine_trade(signals: &[Action]) -> Result<Action> {
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
         debug!("Determine trades with hodl signal");
-        let val = signals.first().ok_or(anyhow!("No hodl signal found"))?;
-        Ok(*val)
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
     }
 }
@@ -1,35 +1,87 @@
 use crate::data::BinanceKline;
+use crate::error::{Error, Result};
 use crate::indicators::BinanceIndicatorInstance;
-use crate::traders::{GenericTrader, StakeSize, TradingFee};
-use anyhow::{anyhow, Result};
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
 use yata::core::{Action, IndicatorResult};
+use yata::helpers::MA;
 use yata::indicators::MACD;
 use yata::prelude::dd::IndicatorInstanceDyn;
 use yata::prelude::*;
 
 use log::debug;
 
-struct IndicatorInstanceWrapper(Box<dyn IndicatorInstanceDyn<BinanceKline>>);
+// Side of the zero line the MACD line is on, Buy above and Sell below
+fn zero_line_trend(macd: f64) -> Action {
+    if macd > 0. {
+        Action::Buy(1)
+    } else if macd < 0. {
+        Action::Sell(1)
+    } else {
+        Action::None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdConfig {
+    pub fast: u8,
+    pub slow: u8,
+    pub signal: u8,
+}
+
+impl Default for MacdConfig {
+    fn default() -> Self {
+        Self { fast: 12, slow: 26, signal: 9 }
+    }
+}
+
+struct IndicatorInstanceWrapper {
+    macd: Box<dyn IndicatorInstanceDyn<BinanceKline>>,
+    zero_line_filter: bool,
+}
 
 impl BinanceIndicatorInstance for IndicatorInstanceWrapper {
+    // With the filter on, the zero line trend is appended as a third signal for `determine_trade`
     fn next_binance_kline(&mut self, candle: &BinanceKline) -> IndicatorResult {
-        self.0.next(candle)
+        let result = self.macd.next(candle);
+        if !self.zero_line_filter {
+            return result;
+        }
+        let trend = zero_line_trend(result.values().first().copied().unwrap_or_default());
+        let signals = [result.signals().first().copied().unwrap_or(Action::None), result.signals().get(1).copied().unwrap_or(Action::None), trend];
+        IndicatorResult::new(result.values(), &signals)
     }
 }
 
 pub struct MACDTrader {
     trading_fee: TradingFee,
     stake_size: StakeSize,
+    settings: TraderSettings,
     indicator: IndicatorInstanceWrapper,
 }
 
 impl MACDTrader {
-    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize) -> Result<Self> {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, config: MacdConfig) -> Result<Self> {
         debug!("Creating a MACD Trader");
-        let macd = MACD::default();
-        let next_kline = kline_feed.first().ok_or(anyhow!("No klines in MACD feed"))?;
+        let MacdConfig { fast, slow, signal } = config;
+        if fast == 0 || signal == 0 || fast >= slow {
+            return Err(Error::InvalidConfig(format!("MACD fast period {fast} must be positive and shorter than slow period {slow}, signal period {signal} must be positive")));
+        }
+
+        let macd = MACD { ma1: MA::EMA(fast), ma2: MA::EMA(slow), signal: MA::EMA(signal), ..MACD::default() };
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in MACD feed")))?;
         let macd = macd.init(next_kline)?;
-        Ok(Self { indicator: IndicatorInstanceWrapper(Box::new(macd)), trading_fee, stake_size })
+        Ok(Self { indicator: IndicatorInstanceWrapper { macd: Box::new(macd), zero_line_filter: false }, trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Only take buys while the MACD line is above zero and sells while it is below
+    pub fn with_zero_line_filter(mut self, zero_line_filter: bool) -> Self {
+        self.indicator.zero_line_filter = zero_line_filter;
+        self
     }
 }
 
@@ -39,16 +91,79 @@ impl GenericTrader for MACDTrader {
     }
 
     fn trading_fee(&self) -> TradingFee {
-        self.trading_fee
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
     }
 
     fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
         &mut self.indicator
     }
 
-    fn determine_trade(signals: &[Action]) -> Result<Action> {
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
         debug!("Determine trades with MACD signal");
-        let val = signals.get(1).ok_or(anyhow!("No MACD signal found"))?;
-        Ok(*val)
+        let val = signals.get(1).copied().unwrap_or(Action::None);
+        let val = match (val, signals.get(2)) {
+            (Action::None, _) | (_, None) | (Action::Buy(_), Some(Action::Buy(_))) | (Action::Sell(_), Some(Action::Sell(_))) => val,
+            (trade, Some(trend)) => {
+                debug!("MACD crossover {trade:?} against the zero line trend {trend:?}");
+                Action::None
+            }
+        };
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_zero_line_filter_suppresses_counter_trend_crossover() -> Result<()> {
+        let below_zero = zero_line_trend(-2.5);
+        assert_eq!(below_zero, Action::Sell(1));
+
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let trader = MACDTrader::new(&klines_from_closes(&[100.0], start_time, Duration::hours(1)), TradingFee::PercentageFee(0.0), StakeSize::FixPercentage(1.), MacdConfig::default())?;
+
+        assert_eq!(trader.determine_trade(&[Action::None, Action::Buy(1)])?, Action::Buy(1));
+        assert_eq!(trader.determine_trade(&[Action::None, Action::Buy(1), below_zero])?, Action::None);
+        assert_eq!(trader.determine_trade(&[Action::None, Action::Sell(1), below_zero])?, Action::Sell(1));
+        assert_eq!(trader.determine_trade(&[Action::None, Action::Buy(1), zero_line_trend(1.0)])?, Action::Buy(1));
+        assert_eq!(trader.determine_trade(&[Action::None, Action::Buy(1), zero_line_trend(0.0)])?, Action::None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_periods_flow_into_macd() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 110.0], start_time, Duration::hours(1));
+
+        let mut trader = MACDTrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixPercentage(1.), MacdConfig { fast: 2, slow: 3, signal: 2 })?;
+        trader.indicator().next_binance_kline(&klines[0]);
+        let result = trader.indicator().next_binance_kline(&klines[1]);
+
+        // Fast and slow EMAs move two thirds and half of the way from 100 to 110
+        assert!((result.values()[0] - 10.0 / 6.0).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_fast_period_not_below_slow() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0], start_time, Duration::hours(1));
+
+        for config in [MacdConfig { fast: 26, slow: 12, signal: 9 }, MacdConfig { fast: 12, slow: 12, signal: 9 }, MacdConfig { signal: 0, ..MacdConfig::default() }] {
+            assert!(matches!(MACDTrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixPercentage(1.), config), Err(Error::InvalidConfig(_))));
+        }
+
+        Ok(())
     }
 }
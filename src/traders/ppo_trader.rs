@@ -0,0 +1,54 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::PpoConfig;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+pub struct PPOTrader {
+    trading_fee: TradingFee,
+    stake_size: StakeSize,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+}
+
+impl PPOTrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, config: PpoConfig) -> Result<Self> {
+        debug!("Creating a PPO Trader");
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in PPO feed")))?;
+        let ppo = config.init(next_kline)?;
+        Ok(Self { indicator: Box::new(ppo), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl GenericTrader for PPOTrader {
+    fn stake_size(&self) -> StakeSize {
+        self.stake_size
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with PPO signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+}
@@ -0,0 +1,87 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::RandomConfig;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+pub struct RandomTrader {
+    trading_fee: TradingFee,
+    stake_size: StakeSize,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+}
+
+impl RandomTrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, config: RandomConfig) -> Result<Self> {
+        debug!("Creating a Random Trader");
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in Random feed")))?;
+        let random = config.init(next_kline)?;
+        Ok(Self { indicator: Box::new(random), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl GenericTrader for RandomTrader {
+    fn stake_size(&self) -> StakeSize {
+        self.stake_size
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with random signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, Position, Trade};
+    use crate::data::generate_klines;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    fn run(seed: u64) -> Result<Vec<Trade>> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = generate_klines(500, start_time, Duration::hours(1), 7);
+        let config = RandomConfig { entry_prob: 0.1, seed };
+        let mut trader = RandomTrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixPercentage(1.), config)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+        for kline in &klines {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+        Ok(account.trade_history)
+    }
+
+    #[test]
+    fn test_same_seed_same_trades() -> Result<()> {
+        let first = run(1)?;
+
+        assert!(first.len() > 10);
+        assert_eq!(first, run(1)?);
+        assert_ne!(first, run(2)?);
+
+        Ok(())
+    }
+}
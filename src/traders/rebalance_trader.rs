@@ -0,0 +1,129 @@
+use crate::account::Account;
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::Cadence;
+use crate::traders::{GenericTrader, Liquidity, StakeSize, TraderSettings, TradingFee};
+use chrono::NaiveDateTime;
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+#[derive(Clone, Copy)]
+pub struct RebalanceConfig {
+    pub target_weight: f64,
+    pub cadence: usize,
+}
+
+pub struct RebalanceTrader {
+    trading_fee: TradingFee,
+    config: RebalanceConfig,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+}
+
+impl RebalanceTrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, config: RebalanceConfig) -> Result<Self> {
+        debug!("Creating a Rebalance Trader");
+        if !(0.0..=1.0).contains(&config.target_weight) {
+            return Err(Error::InvalidConfig(String::from("Rebalance target weight must be between 0 and 1")));
+        }
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in Rebalance feed")))?;
+        let cadence = Cadence::new(config.cadence).init(next_kline)?;
+        Ok(Self { indicator: Box::new(cadence), trading_fee, config, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    fn rebalance(&self, timestamp: NaiveDateTime, price: f64, account: &mut Account) -> Result<()> {
+        let position_value = account.position.quantity * price;
+        let equity = account.available_fund + position_value;
+        let drift = equity * self.config.target_weight - position_value;
+        let fee = self.trading_fee.compute(drift.abs(), account.recent_volume(timestamp), Liquidity::Taker);
+
+        if drift > 0. {
+            let notional = drift.min(account.available_fund - fee);
+            if notional > 0. {
+                debug!("{timestamp}, rebalance B {:.08} @ ${price:.08}", notional / price);
+                account.open(timestamp, notional / price, price, fee)?;
+            }
+        } else if drift < 0. {
+            debug!("{timestamp}, rebalance S {:.08} @ ${price:.08}", -drift / price);
+            account.close(timestamp, -drift / price, price, fee)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl GenericTrader for RebalanceTrader {
+    fn stake_size(&self) -> StakeSize {
+        StakeSize::FixPercentage(self.config.target_weight)
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with rebalance cadence signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+
+    fn next_trade_session(&mut self, account: &mut Account, kline: &BinanceKline) -> Result<()> {
+        if account.is_halted() {
+            debug!("Trading halted");
+            return Ok(());
+        }
+
+        let indicator = self.indicator().next_binance_kline(kline);
+        let trade = self.determine_trade(indicator.signals())?;
+        if trade != Action::None {
+            self.rebalance(kline.timestamp(self.settings.timestamp_convention), kline.close, account)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Position;
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_rebalance_sells_down_to_target_weight() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 200.0], start_time, Duration::days(1));
+        let config = RebalanceConfig { target_weight: 0.5, cadence: 1 };
+        let mut trader = RebalanceTrader::new(&klines, TradingFee::PercentageFee(0.0), config)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        for kline in &klines {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+
+        assert_eq!(account.position.quantity, 3.75);
+        assert_eq!(account.available_fund, 750.0);
+        assert_eq!(account.position.quantity * 200.0 / (account.available_fund + account.position.quantity * 200.0), 0.5);
+
+        Ok(())
+    }
+}
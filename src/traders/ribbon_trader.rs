@@ -0,0 +1,86 @@
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::RibbonConfig;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+pub struct RibbonTrader {
+    trading_fee: TradingFee,
+    stake_size: StakeSize,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+}
+
+impl RibbonTrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, config: RibbonConfig) -> Result<Self> {
+        debug!("Creating a Ribbon Trader");
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in Ribbon feed")))?;
+        let ribbon = config.init(next_kline)?;
+        Ok(Self { indicator: Box::new(ribbon), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl GenericTrader for RibbonTrader {
+    fn stake_size(&self) -> StakeSize {
+        self.stake_size
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with EMA ribbon signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, BuySellIndicator, Position};
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_stacked_trend_enters_and_chop_stays_quiet() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let trend = (0..80).map(|i| 100. + i as f64);
+        let chop = (0..80).map(|i| 179. + 5. * (2. * PI * i as f64 / 10.).sin());
+        let closes: Vec<f64> = trend.chain(chop).collect();
+        let klines = klines_from_closes(&closes, start_time, Duration::hours(1));
+        let mut trader = RibbonTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixAmount(100.0), RibbonConfig::default())?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        for kline in &klines {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+
+        assert_eq!(account.trade_history.len(), 1);
+        assert_eq!(account.trade_history[0].buy_sell_indicator, BuySellIndicator::Buy);
+        assert_eq!(account.trade_history[0].timestamp, klines[59].end_time);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::account::BuySellIndicator;
+use crate::rng::SplitMix64;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Slippage {
+    #[default]
+    None,
+    /// Perturbs every fill price with Gaussian noise of `bps_stddev` basis points, the same seed always yields the same fills
+    StochasticFill { bps_stddev: f64, seed: u64 },
+}
+
+impl Slippage {
+    pub fn fill_price(&self, price: f64, fill_index: usize) -> f64 {
+        match *self {
+            Slippage::None => price,
+            Slippage::StochasticFill { bps_stddev, seed } => {
+                let noise = SplitMix64::at_position(seed, 2 * fill_index as u64).next_gaussian();
+                price * (1. + noise * bps_stddev / 10_000.)
+            }
+        }
+    }
+}
+
+/// Bid-ask spread around the mid price: buys fill at the ask, sells at the bid.
+/// Unlike slippage it always works against the trade, a flat round trip loses the whole spread.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Spread {
+    #[default]
+    None,
+    /// Full spread in quote currency
+    Fixed(f64),
+    /// Full spread in basis points of the mid price
+    Bps(f64),
+}
+
+impl Spread {
+    pub fn fill_price(&self, mid: f64, side: BuySellIndicator) -> f64 {
+        let half_spread = match *self {
+            Spread::None => 0.,
+            Spread::Fixed(spread) => spread / 2.,
+            Spread::Bps(bps) => mid * bps / 20_000.,
+        };
+        match side {
+            BuySellIndicator::Buy => mid + half_spread,
+            BuySellIndicator::Sell => mid - half_spread,
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use crate::data::BinanceKline;
+use crate::error::{Error, Result};
 use crate::indicators::BinanceIndicatorInstance;
 use crate::indicators::Sma2Pair;
-use crate::traders::{GenericTrader, StakeSize, TradingFee};
-use anyhow::{anyhow, Result};
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
 use yata::core::Action;
 use yata::prelude::*;
 
@@ -11,18 +11,27 @@ use log::debug;
 pub struct SMA2Trader {
     trading_fee: TradingFee,
     stake_size: StakeSize,
+    settings: TraderSettings,
     indicator: Box<dyn BinanceIndicatorInstance>,
 }
 
 impl SMA2Trader {
-    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee) -> Result<Self> {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, short_window: usize, long_window: usize) -> Result<Self> {
         debug!("Creating a SMA2 Trader");
+        if short_window == 0 || short_window >= long_window {
+            return Err(Error::InvalidConfig(format!("SMA2 short window {short_window} must be positive and shorter than long window {long_window}")));
+        }
 
-        let sma_pair = Sma2Pair::new(1, 2);
+        let sma_pair = Sma2Pair::new(short_window, long_window);
 
-        let next_kline = kline_feed.first().ok_or(anyhow!("No klines in SMA2 feed"))?;
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in SMA2 feed")))?;
         let sma = sma_pair.init(next_kline)?;
-        Ok(Self { indicator: Box::new(sma), trading_fee, stake_size: StakeSize::FixAmount(100.0) })
+        Ok(Self { indicator: Box::new(sma), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
     }
 }
 
@@ -32,16 +41,20 @@ impl GenericTrader for SMA2Trader {
     }
 
     fn trading_fee(&self) -> TradingFee {
-        self.trading_fee
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
     }
 
     fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
         self.indicator.as_mut()
     }
 
-    fn determine_trade(signals: &[Action]) -> Result<Action> {
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
         debug!("Determine trades with SMA2 signal");
-        let val = signals.first().ok_or(anyhow!("No SMA2 signal found"))?;
-        Ok(*val)
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
     }
 }
@@ -1,8 +1,8 @@
 use crate::data::BinanceKline;
+use crate::error::{Error, Result};
 use crate::indicators::BinanceIndicatorInstance;
 use crate::indicators::SmaPair;
-use crate::traders::{GenericTrader, StakeSize, TradingFee};
-use anyhow::{anyhow, Result};
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
 use yata::core::Action;
 use yata::prelude::*;
 
@@ -11,18 +11,27 @@ use log::debug;
 pub struct SMATrader {
     trading_fee: TradingFee,
     stake_size: StakeSize,
+    settings: TraderSettings,
     indicator: Box<dyn BinanceIndicatorInstance>,
 }
 
 impl SMATrader {
-    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee) -> Result<Self> {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, short_window: u8, long_window: u8) -> Result<Self> {
         debug!("Creating a SMA Trader");
+        if short_window == 0 || short_window >= long_window {
+            return Err(Error::InvalidConfig(format!("SMA short window {short_window} must be positive and shorter than long window {long_window}")));
+        }
 
-        let sma_pair = SmaPair::new(1, 2);
+        let sma_pair = SmaPair::new(short_window, long_window);
 
-        let next_kline = kline_feed.first().ok_or(anyhow!("No klines in SMA feed"))?;
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in SMA feed")))?;
         let sma = sma_pair.init(next_kline)?;
-        Ok(Self { indicator: Box::new(sma), trading_fee, stake_size: StakeSize::FixAmount(100.0) })
+        Ok(Self { indicator: Box::new(sma), trading_fee, stake_size, settings: TraderSettings::default() })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
     }
 }
 
@@ -32,16 +41,57 @@ impl GenericTrader for SMATrader {
     }
 
     fn trading_fee(&self) -> TradingFee {
-        self.trading_fee
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
     }
 
     fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
         self.indicator.as_mut()
     }
 
-    fn determine_trade(signals: &[Action]) -> Result<Action> {
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
         debug!("Determine trades with SMA signal");
-        let val = signals.first().ok_or(anyhow!("No SMA signal found"))?;
-        Ok(*val)
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::klines_from_closes;
+    use crate::traders::SMA2Trader;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_custom_stake_size_is_used() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 110.0], start_time, Duration::hours(1));
+
+        let trader = SMATrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixPercentage(0.25), 1, 2)?;
+
+        assert_eq!(trader.stake_size(), StakeSize::FixPercentage(0.25));
+        assert_eq!(trader.trading_fee(), TradingFee::PercentageFee(0.001));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_windows_flow_through_and_are_validated() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 110.0], start_time, Duration::hours(1));
+
+        let mut trader = SMATrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixAmount(100.0), 50, 200)?;
+        assert_eq!(trader.warm_up_period(), 200);
+
+        assert!(matches!(SMATrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixAmount(100.0), 200, 50), Err(Error::InvalidConfig(_))));
+        assert!(matches!(SMATrader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixAmount(100.0), 50, 50), Err(Error::InvalidConfig(_))));
+        assert!(matches!(SMA2Trader::new(&klines, TradingFee::PercentageFee(0.001), StakeSize::FixAmount(100.0), 0, 2), Err(Error::InvalidConfig(_))));
+
+        Ok(())
     }
 }
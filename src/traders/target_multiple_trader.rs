@@ -0,0 +1,136 @@
+use crate::account::Account;
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::indicators::Hodl;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+use yata::prelude::*;
+
+use log::debug;
+
+#[derive(Clone, Copy)]
+pub struct TargetConfig {
+    /// Mark price over entry price at which the whole position is sold, 3.0 sells at a 3x
+    pub multiple: f64,
+}
+
+pub struct TargetMultipleTrader {
+    trading_fee: TradingFee,
+    stake_size: StakeSize,
+    config: TargetConfig,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+    entered: bool,
+}
+
+impl TargetMultipleTrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, config: TargetConfig) -> Result<Self> {
+        debug!("Creating a Target Multiple Trader");
+        if config.multiple <= 1.0 {
+            return Err(Error::InvalidConfig(String::from("Target multiple must be above 1")));
+        }
+        let next_kline = kline_feed.first().ok_or(Error::NoData(String::from("No klines in Target Multiple feed")))?;
+        let hodl = Hodl.init(next_kline)?;
+        Ok(Self { indicator: Box::new(hodl), trading_fee, stake_size, config, settings: TraderSettings::default(), entered: false })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+}
+
+impl GenericTrader for TargetMultipleTrader {
+    fn stake_size(&self) -> StakeSize {
+        self.stake_size
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with hodl signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+
+    // Buys once on the first signal, then only the mark price against the entry decides the exit
+    fn next_trade_session(&mut self, account: &mut Account, kline: &BinanceKline) -> Result<()> {
+        if account.is_halted() {
+            debug!("Trading halted");
+            return Ok(());
+        }
+
+        let timestamp_convention = self.settings.timestamp_convention;
+        let timestamp = kline.timestamp(timestamp_convention);
+        let price = kline.close;
+
+        self.indicator().set_timestamp_convention(timestamp_convention);
+        let indicator = self.indicator().next_binance_kline(kline);
+        let trade = self.determine_trade(indicator.signals())?;
+
+        if account.position.quantity > 0. {
+            let target = account.position.cost * self.config.multiple;
+            if price >= target {
+                debug!("{timestamp}, target {:.02}x reached at ${price:.08}", self.config.multiple);
+                self.execute_sell(timestamp, price, 1., account)?;
+            }
+        } else if !self.entered && matches!(trade, Action::Buy(_)) {
+            self.execute_buy(timestamp, price, 1., account)?;
+            self.entered = true;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{BuySellIndicator, Position};
+    use crate::data::klines_from_closes;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_position_is_sold_when_target_multiple_is_reached() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0, 200.0, 290.0, 300.0, 400.0], start_time, Duration::days(1));
+        let config = TargetConfig { multiple: 3.0 };
+        let mut trader = TargetMultipleTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixAmount(500.0), config)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        for kline in &klines[..3] {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+        assert_eq!(account.trade_history.len(), 1);
+        assert_eq!(account.position.quantity, 5.0);
+
+        for kline in &klines[3..] {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+
+        assert_eq!(account.trade_history.len(), 2);
+        let exit = &account.trade_history[1];
+        assert_eq!(exit.buy_sell_indicator, BuySellIndicator::Sell);
+        assert_eq!(exit.quantity, 5.0);
+        assert_eq!(exit.price, 300.0);
+        assert_eq!(exit.timestamp, klines[3].end_time);
+        assert_eq!(account.position.quantity, 0.0);
+        assert_eq!(account.available_fund, 2000.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::account::Account;
+use crate::data::BinanceKline;
+use crate::error::{Error, Result};
+use crate::indicators::BinanceIndicatorInstance;
+use crate::traders::{GenericTrader, StakeSize, TraderSettings, TradingFee};
+use yata::core::Action;
+
+use log::debug;
+
+pub struct TwapConfig {
+    /// Number of candles the stake is spread over, one equal slice at each close
+    pub slices: usize,
+    /// Indicator whose buy signal starts a new accumulation and whose sell signal exits
+    pub trigger: Box<dyn BinanceIndicatorInstance>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PendingSlices {
+    notional: f64,
+    remaining: usize,
+}
+
+pub struct TwapTrader {
+    trading_fee: TradingFee,
+    stake_size: StakeSize,
+    slices: usize,
+    settings: TraderSettings,
+    indicator: Box<dyn BinanceIndicatorInstance>,
+    pending: Option<PendingSlices>,
+}
+
+impl TwapTrader {
+    pub fn new(kline_feed: &[BinanceKline], trading_fee: TradingFee, stake_size: StakeSize, config: TwapConfig) -> Result<Self> {
+        debug!("Creating a TWAP Trader");
+        if config.slices == 0 {
+            return Err(Error::InvalidConfig(String::from("TWAP needs at least one slice")));
+        }
+        if kline_feed.is_empty() {
+            return Err(Error::NoData(String::from("No klines in TWAP feed")));
+        }
+        Ok(Self { indicator: config.trigger, trading_fee, stake_size, slices: config.slices, settings: TraderSettings::default(), pending: None })
+    }
+
+    pub fn with_settings(mut self, settings: TraderSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Slices still to be executed for the current accumulation
+    pub fn remaining_slices(&self) -> usize {
+        self.pending.map_or(0, |pending| pending.remaining)
+    }
+}
+
+impl GenericTrader for TwapTrader {
+    // While accumulating each slice buys a fixed share of the stake resolved at the decision
+    fn stake_size(&self) -> StakeSize {
+        match self.pending {
+            Some(pending) => StakeSize::FixAmount(pending.notional),
+            None => self.stake_size,
+        }
+    }
+
+    fn trading_fee(&self) -> TradingFee {
+        self.trading_fee.clone()
+    }
+
+    fn settings(&self) -> &TraderSettings {
+        &self.settings
+    }
+
+    fn indicator(&mut self) -> &mut dyn BinanceIndicatorInstance {
+        self.indicator.as_mut()
+    }
+
+    fn determine_trade(&self, signals: &[Action]) -> Result<Action> {
+        debug!("Determine trades with TWAP trigger signal");
+        let val = signals.first().copied().unwrap_or(Action::None);
+        Ok(val)
+    }
+
+    fn next_trade_session(&mut self, account: &mut Account, kline: &BinanceKline) -> Result<()> {
+        if account.is_halted() {
+            debug!("Trading halted");
+            return Ok(());
+        }
+
+        let timestamp_convention = self.settings.timestamp_convention;
+        let timestamp = kline.timestamp(timestamp_convention);
+        let price = kline.close;
+
+        self.indicator().set_timestamp_convention(timestamp_convention);
+        let indicator = self.indicator().next_binance_kline(kline);
+        match self.determine_trade(indicator.signals())? {
+            Action::Buy(_) if self.pending.is_some() => debug!("{timestamp}, already accumulating, {} slices left", self.remaining_slices()),
+            Action::Buy(_) => {
+                let stake = self.stake_size.stake(account).min(account.available_fund * (1. - self.settings.reserve_pct)).max(0.);
+                debug!("{timestamp}, TWAP accumulation of ${stake:.02} over {} candles", self.slices);
+                self.pending = Some(PendingSlices { notional: stake / self.slices as f64, remaining: self.slices });
+            }
+            Action::Sell(strength) => {
+                self.pending = None;
+                self.execute_sell(timestamp, price, self.settings.strength_scale.fraction(strength, indicator.values()), account)?;
+            }
+            _ => debug!("Nothing to do"),
+        }
+
+        if let Some(pending) = self.pending {
+            self.execute_buy(timestamp, price, 1., account)?;
+            self.pending = Some(PendingSlices { remaining: pending.remaining - 1, ..pending }).filter(|pending| pending.remaining > 0);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{BuySellIndicator, Position};
+    use crate::data::klines_from_closes;
+    use crate::indicators::Cadence;
+    use anyhow::{anyhow, Result};
+    use chrono::{Duration, NaiveDate};
+    use yata::prelude::*;
+
+    #[test]
+    fn test_buy_decision_is_split_into_equal_slices() -> Result<()> {
+        let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Cannot create timestamp"))?;
+        let klines = klines_from_closes(&[100.0; 6], start_time, Duration::hours(1));
+        let trigger = Cadence::new(100).init(&klines[0])?;
+        let config = TwapConfig { slices: 4, trigger: Box::new(trigger) };
+        let mut trader = TwapTrader::new(&klines, TradingFee::PercentageFee(0.0), StakeSize::FixAmount(400.0), config)?;
+        let mut account = Account::new(1000.0, Position { quantity: 0.0, cost: 0.0 }, start_time);
+
+        for kline in &klines {
+            trader.next_trade_session(&mut account, kline)?;
+            account.mark_to_market(kline.end_time, kline.close)?;
+        }
+
+        assert_eq!(account.trade_history.len(), 4);
+        for (trade, kline) in account.trade_history.iter().zip(&klines) {
+            assert_eq!(trade.buy_sell_indicator, BuySellIndicator::Buy);
+            assert_eq!(trade.quantity, 1.0);
+            assert_eq!(trade.timestamp, kline.end_time);
+        }
+        assert_eq!(trader.remaining_slices(), 0);
+        assert_eq!(account.position.quantity, 4.0);
+        assert_eq!(account.available_fund, 600.0);
+
+        Ok(())
+    }
+}
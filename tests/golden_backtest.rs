@@ -0,0 +1,112 @@
+//! Golden-file regression test: the five benchmark strategies over a seeded synthetic dataset must keep
+//! their recorded final equity and trade count, catching silent changes in fee math, signal timing and PnL.
+//!
+//! When a change is meant to alter results, regenerate the golden file and commit it with the change:
+//! `UPDATE_GOLDEN=1 cargo test --test golden_backtest`
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate};
+
+use crypto_strategy_analysis::account::{Account, Position};
+use crypto_strategy_analysis::backtest::loop_kline;
+use crypto_strategy_analysis::data::{generate_klines, BinanceKline};
+use crypto_strategy_analysis::traders::{DCATrader, GenericTrader, HODLTrader, MACDTrader, MacdConfig, SMA2Trader, SMATrader, StakeSize, TradingFee};
+
+const SEED: u64 = 42;
+const CANDLES: usize = 5_000;
+const START_FUND: f64 = 1000.0;
+const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/backtest.csv");
+const HEADER: &str = "strategy,final_equity,trade_count";
+
+#[derive(Debug, PartialEq)]
+struct GoldenRow {
+    strategy: String,
+    final_equity: f64,
+    trade_count: usize,
+}
+
+fn synthetic_klines() -> Result<Vec<BinanceKline>> {
+    let start_time = NaiveDate::from_ymd_opt(2024, 1, 1).and_then(|d| d.and_hms_opt(0, 0, 0)).ok_or(anyhow!("Invalid start time"))?;
+    Ok(generate_klines(CANDLES, start_time, Duration::hours(1), SEED))
+}
+
+fn run<T: GenericTrader>(mut trader: T, strategy: &str, klines: &[BinanceKline]) -> Result<GoldenRow> {
+    let first_kline = klines.first().ok_or(anyhow!("No klines generated"))?;
+    let mut account = Account::new(START_FUND, Position { quantity: 0.0, cost: 0.0 }, first_kline.start_time);
+    loop_kline(&mut trader, &mut account, strategy, klines)?;
+    let final_equity = account.profit_and_loss_history.last().map(|time_value| time_value.equity).ok_or(anyhow!("No equity history for {strategy}"))?;
+    Ok(GoldenRow { strategy: strategy.to_string(), final_equity, trade_count: account.trade_history.len() })
+}
+
+fn backtest_all(klines: &[BinanceKline]) -> Result<Vec<GoldenRow>> {
+    let fee = || TradingFee::PercentageFee(0.005);
+    Ok(vec![
+        run(MACDTrader::new(klines, fee(), StakeSize::FixPercentage(1.), MacdConfig::default())?, "MACD", klines)?,
+        run(HODLTrader::new(klines, fee(), StakeSize::FixPercentage(1.))?, "HODL", klines)?,
+        run(DCATrader::new(klines, fee(), StakeSize::FixAmount(100.0))?, "DCA", klines)?,
+        run(SMATrader::new(klines, fee(), StakeSize::FixAmount(100.0), 1, 2)?, "SMA", klines)?,
+        run(SMA2Trader::new(klines, fee(), StakeSize::FixAmount(100.0), 1, 2)?, "SMA2", klines)?,
+    ])
+}
+
+// Display of f64 is the shortest representation that parses back to the same value
+fn format_golden(rows: &[GoldenRow]) -> String {
+    let lines: Vec<String> = rows.iter().map(|row| format!("{},{},{}", row.strategy, row.final_equity, row.trade_count)).collect();
+    format!("{HEADER}\n{}\n", lines.join("\n"))
+}
+
+fn parse_golden(content: &str) -> Result<Vec<GoldenRow>> {
+    content
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.trim().split(',').collect();
+            let [strategy, final_equity, trade_count] = fields[..] else {
+                return Err(anyhow!("Malformed golden line: {line}"));
+            };
+            Ok(GoldenRow { strategy: strategy.to_string(), final_equity: final_equity.parse()?, trade_count: trade_count.parse()? })
+        })
+        .collect()
+}
+
+#[test]
+fn test_strategies_match_golden_values() -> Result<()> {
+    let klines = synthetic_klines()?;
+    let actual = backtest_all(&klines)?;
+
+    let golden_path = Path::new(GOLDEN_PATH);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        if let Some(parent) = golden_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(golden_path, format_golden(&actual))?;
+        eprintln!("Recorded golden values in {GOLDEN_PATH}, review and commit the file");
+        return Ok(());
+    }
+
+    let golden = fs::read_to_string(golden_path).map_err(|e| anyhow!("Cannot read golden file {GOLDEN_PATH} ({e}), record it with UPDATE_GOLDEN=1 and commit it"))?;
+    let expected = parse_golden(&golden)?;
+    assert_eq!(actual.len(), expected.len(), "strategy set changed, regenerate with UPDATE_GOLDEN=1");
+    for (actual, expected) in actual.iter().zip(&expected) {
+        assert_eq!(actual.strategy, expected.strategy);
+        assert_eq!(actual.trade_count, expected.trade_count, "{} trade count changed", actual.strategy);
+        let tolerance = 1e-9 * expected.final_equity.abs().max(1.);
+        assert!((actual.final_equity - expected.final_equity).abs() <= tolerance, "{} final equity {} != golden {}", actual.strategy, actual.final_equity, expected.final_equity);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_synthetic_dataset_is_reproducible() -> Result<()> {
+    assert_eq!(synthetic_klines()?, synthetic_klines()?);
+
+    let rows = backtest_all(&synthetic_klines()?)?;
+    assert_eq!(parse_golden(&format_golden(&rows))?, rows);
+
+    Ok(())
+}